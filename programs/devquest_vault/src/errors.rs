@@ -6,6 +6,7 @@ use anchor_lang::prelude::*;
 /// Custom errors for the vault program
 #[error_code]
 pub enum CustomError {
+    // Unused since `init` guards re-initialization; kept so error codes stay stable
     #[msg("Vault is already initialized")]
     AlreadyInitialized,
     #[msg("Only admin can perform this action")]
@@ -30,4 +31,72 @@ pub enum CustomError {
     EpochSpendingLimitReached,
     #[msg("Invalid epoch configuration")]
     InvalidEpochConfig,
+    #[msg("Vault has insufficient funds")]
+    InsufficientFunds,
+    #[msg("Only whitelisted depositors can deposit")]
+    UnauthorizedDepositor,
+    #[msg("Maximum number of depositors reached")]
+    MaxDepositorsReached,
+    #[msg("Depositor already exists")]
+    DepositorAlreadyExists,
+    #[msg("Depositor not found")]
+    DepositorNotFound,
+    #[msg("Payee has no active payout schedule")]
+    NoScheduleForPayee,
+    #[msg("Payout cliff not reached")]
+    CliffNotReached,
+    #[msg("Invalid admin")]
+    InvalidAdmin,
+    #[msg("Only the pending admin can accept the handoff")]
+    NotPendingAdmin,
+    #[msg("Deposit would exceed the vault balance cap")]
+    DepositCapExceeded,
+    #[msg("Withdrawal exceeds the instant limit and must be requested first")]
+    WithdrawalDelayRequired,
+    #[msg("A withdrawal is already pending")]
+    PendingWithdrawalExists,
+    #[msg("No pending withdrawal")]
+    NoPendingWithdrawal,
+    #[msg("Withdrawal delay has not elapsed")]
+    WithdrawalDelayNotElapsed,
+    #[msg("Invalid withdrawal delay")]
+    InvalidWithdrawalDelay,
+    #[msg("Vault is paused")]
+    VaultPaused,
+    #[msg("Vault state is already at the current version")]
+    AlreadyMigrated,
+    #[msg("Unrecognized vault state layout")]
+    UnsupportedVersion,
+    #[msg("Vault name must be valid UTF-8")]
+    InvalidVaultName,
+    #[msg("Invalid payout split")]
+    InvalidSplit,
+    #[msg("Beneficiary account does not match the requested beneficiary")]
+    InvalidBeneficiary,
+    #[msg("Global spending limit reached")]
+    GlobalLimitReached,
+    #[msg("Schedule has no fallback recipient")]
+    NoFallbackRecipient,
+    #[msg("Grace period has not elapsed")]
+    GracePeriodNotElapsed,
+    #[msg("Withdrawals require a memo")]
+    MemoRequired,
+    #[msg("Source and destination vaults must differ")]
+    SameVaultTransfer,
+    #[msg("An admin handoff is in progress")]
+    HandoffInProgress,
+    #[msg("Token accounts for the schedule's mint are missing or invalid")]
+    InvalidTokenAccount,
+    #[msg("Snapshot slot is in the future or too old")]
+    InvalidSnapshotSlot,
+    #[msg("The current epoch has not elapsed yet")]
+    EpochNotElapsed,
+    #[msg("Stored bumps do not match the canonical PDA bumps")]
+    BumpMismatch,
+    #[msg("Signer is not the payee's registered claim delegate")]
+    UnauthorizedDelegate,
+    #[msg("Amount is within the instant limit and can be withdrawn directly")]
+    WithinInstantLimit,
+    #[msg("Vault nonce was already used by this admin")]
+    StaleVaultNonce,
 }
\ No newline at end of file
@@ -0,0 +1,131 @@
+// src/events.rs
+// Event definitions for the vault program
+
+use anchor_lang::prelude::*;
+use crate::state::PayoutKind;
+
+/// Emitted when a new vault is initialized
+#[event]
+pub struct VaultInitializedEvent {
+    pub vault_state: Pubkey,
+    pub admin: Pubkey,
+    pub name: [u8; 32],
+    pub vault_nonce: u64,
+}
+
+/// Emitted when a vault is closed, with the account that received its funds
+#[event]
+pub struct VaultClosedEvent {
+    pub vault_state: Pubkey,
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+}
+
+/// Emitted for every withdrawal, with the running total of tracked withdrawals
+#[event]
+pub struct WithdrawEvent {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub is_admin: bool,
+    pub total_withdrawn: u64,
+    pub memo: [u8; 64],
+}
+
+/// Emitted when a payee claims a scheduled payout or stream
+#[event]
+pub struct PayoutClaimedEvent {
+    pub payee: Pubkey,
+    pub amount: u64,                 // Amount paid by this claim
+    pub deferred: u64,               // Amount due but held back by the schedule's max_per_claim
+    pub token_amount: u64,           // Tokens paid alongside by a dual-asset schedule
+}
+
+/// Emitted when a payee tries to claim a payout before it is due
+#[event]
+pub struct ClaimAttempt {
+    pub payee: Pubkey,
+    pub next_payout_time: i64,
+    pub attempted_at: i64,
+}
+
+/// Emitted when the admin adds a payee
+#[event]
+pub struct PayeeAddedEvent {
+    pub payee: Pubkey,
+    pub label: [u8; 32],
+}
+
+/// Emitted when the admin removes a payee
+#[event]
+pub struct PayeeRemovedEvent {
+    pub payee: Pubkey,
+    pub label: [u8; 32],
+}
+
+/// Emitted when the admin schedules a payout
+#[event]
+pub struct PayoutScheduledEvent {
+    pub payee: Pubkey,
+    pub kind: PayoutKind,
+    pub interval: i64,
+    pub next_payout_time: i64,
+}
+
+/// Emitted when the admin changes the amount or interval of a payout schedule
+#[event]
+pub struct PayoutUpdatedEvent {
+    pub payee: Pubkey,
+    pub kind: PayoutKind,
+    pub interval: i64,
+    pub next_payout_time: i64,
+}
+
+/// Emitted when the admin moves a payout schedule to another payee
+#[event]
+pub struct PayoutReassignedEvent {
+    pub from_payee: Pubkey,
+    pub to_payee: Pubkey,
+    pub next_payout_time: i64,
+}
+
+/// Emitted when the admin cancels a payout schedule
+#[event]
+pub struct PayoutCancelledEvent {
+    pub payee: Pubkey,
+    pub kind: PayoutKind,
+    pub interval: i64,
+    pub next_payout_time: i64,
+}
+
+/// Emitted when the admin redirects an unclaimed payout to the schedule's fallback recipient
+#[event]
+pub struct PayoutEscheatedEvent {
+    pub payee: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub next_payout_time: i64,
+}
+
+/// Emitted when the admin moves funds from one of their vaults to another
+#[event]
+pub struct VaultTransferEvent {
+    pub source_vault_state: Pubkey,
+    pub destination_vault_state: Pubkey,
+    pub amount: u64,
+}
+
+/// Emitted when the admin cancels a stream, with what stays owed to the payee and what returns to the vault
+#[event]
+pub struct StreamCancelledEvent {
+    pub payee: Pubkey,
+    pub settled: u64,                // Vested but unclaimed, still claimable by the payee
+    pub refunded: u64,               // Unvested remainder kept by the vault
+}
+
+/// Emitted when outflow within the velocity window exceeds the limit and the vault pauses itself
+#[event]
+pub struct CircuitBreakerTripped {
+    pub outflow: u64,                // Rolling outflow, including the tripping payment
+    pub limit: u64,
+    pub window: i64,
+}
\ No newline at end of file
@@ -0,0 +1,73 @@
+// src/instructions/admin.rs
+// Admin handoff instruction implementation
+
+use anchor_lang::prelude::*;
+use crate::{errors::CustomError, state::VaultState};
+
+/// Accounts required for proposing an admin handoff
+#[derive(Accounts)]
+pub struct UpdateAdmin<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"state", vault_state.creator.as_ref(), VaultState::nonce_seed(vault_state.vault_nonce).as_ref()],
+        bump = vault_state.state_bump,
+        constraint = user.key() == vault_state.admin @ CustomError::UnauthorizedAdmin,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+}
+
+/// Accounts required for accepting a pending admin handoff
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    /// The proposed admin accepting the role
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"state", vault_state.creator.as_ref(), VaultState::nonce_seed(vault_state.vault_nonce).as_ref()],
+        bump = vault_state.state_bump,
+        constraint = vault_state.pending_admin == Some(user.key()) @ CustomError::NotPendingAdmin,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+}
+
+pub fn propose_admin(ctx: Context<UpdateAdmin>, new_admin: Pubkey) -> Result<()> {
+    ctx.accounts.propose_admin(new_admin)
+}
+
+pub fn cancel_admin_proposal(ctx: Context<UpdateAdmin>) -> Result<()> {
+    ctx.accounts.cancel_admin_proposal()
+}
+
+pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+    ctx.accounts.accept_admin()
+}
+
+impl<'info> UpdateAdmin<'info> {
+    /// Handler for proposing a new admin, who must accept before taking over (admin only)
+    pub fn propose_admin(&mut self, new_admin: Pubkey) -> Result<()> {
+        // A zero admin would permanently lock every admin-only instruction, and
+        // proposing the current admin would be a no-op handoff
+        require!(new_admin != Pubkey::default(), CustomError::InvalidAdmin);
+        require!(new_admin != self.vault_state.admin, CustomError::InvalidAdmin);
+        self.vault_state.pending_admin = Some(new_admin);
+        Ok(())
+    }
+
+    /// Handler for withdrawing a pending admin proposal (admin only)
+    pub fn cancel_admin_proposal(&mut self) -> Result<()> {
+        self.vault_state.pending_admin = None;
+        Ok(())
+    }
+}
+
+impl<'info> AcceptAdmin<'info> {
+    /// Handler for completing an admin handoff (pending admin only)
+    pub fn accept_admin(&mut self) -> Result<()> {
+        self.vault_state.admin = self.user.key();
+        self.vault_state.pending_admin = None;
+        Ok(())
+    }
+}
\ No newline at end of file
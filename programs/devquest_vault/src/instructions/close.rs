@@ -2,7 +2,7 @@
 // Close instruction implementation
 
 use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
-use crate::{errors::CustomError, state::VaultState};
+use crate::{errors::CustomError, events::VaultClosedEvent, state::VaultState};
 
 /// Accounts required for closing the vault
 #[derive(Accounts)]
@@ -17,7 +17,37 @@ pub struct Close<'info> {
     pub vault: SystemAccount<'info>,
     #[account(
         mut,
-        seeds = [b"state", vault_state.admin.key().as_ref()],
+        seeds = [b"state", vault_state.creator.as_ref(), VaultState::nonce_seed(vault_state.vault_nonce).as_ref()],
+        bump = vault_state.state_bump,
+        close = user,
+        constraint = user.key() == vault_state.admin @ CustomError::UnauthorizedAdmin,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts required for closing the vault and sending its funds to a beneficiary
+#[derive(Accounts)]
+#[instruction(beneficiary: Pubkey)]
+pub struct CloseTo<'info> {
+    /// The admin closing the vault; receives the state account's rent
+    #[account(mut)]
+    pub user: Signer<'info>,
+    /// The account receiving the vault's lamports
+    #[account(
+        mut,
+        constraint = beneficiary_account.key() == beneficiary @ CustomError::InvalidBeneficiary,
+    )]
+    pub beneficiary_account: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", vault_state.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"state", vault_state.creator.as_ref(), VaultState::nonce_seed(vault_state.vault_nonce).as_ref()],
         bump = vault_state.state_bump,
         close = user,
         constraint = user.key() == vault_state.admin @ CustomError::UnauthorizedAdmin,
@@ -30,22 +60,62 @@ pub fn handler(ctx: Context<Close>) -> Result<()> {
     ctx.accounts.close()
 }
 
+pub fn close_to(ctx: Context<CloseTo>, _beneficiary: Pubkey) -> Result<()> {
+    ctx.accounts.close_to()
+}
+
 impl<'info> Close<'info> {
     /// Handler for closing the vault and returning all funds to the admin
     pub fn close(&mut self) -> Result<()> {
-        let cpi_program = self.system_program.to_account_info();
-        let cpi_accounts = Transfer {
-            from: self.vault.to_account_info(),
-            to: self.user.to_account_info(),
-        };
-        let seeds = &[
-            b"vault",
-            self.vault_state.to_account_info().key.as_ref(),
-            &[self.vault_state.vault_bump],
-        ];
-        let signer_seeds = &[&seeds[..]];
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
-        transfer(cpi_ctx, self.vault.lamports())?;
-        Ok(())
+        drain_vault(
+            &self.vault,
+            &self.vault_state,
+            &self.system_program,
+            self.user.to_account_info(),
+        )
     }
+}
+
+impl<'info> CloseTo<'info> {
+    /// Handler for closing the vault and sending all funds to the beneficiary (admin only)
+    pub fn close_to(&mut self) -> Result<()> {
+        drain_vault(
+            &self.vault,
+            &self.vault_state,
+            &self.system_program,
+            self.beneficiary_account.to_account_info(),
+        )
+    }
+}
+
+/// Transfers every lamport in the vault PDA to `beneficiary`
+fn drain_vault<'info>(
+    vault: &SystemAccount<'info>,
+    vault_state: &Account<'info, VaultState>,
+    system_program: &Program<'info, System>,
+    beneficiary: AccountInfo<'info>,
+) -> Result<()> {
+    // The incoming admin must not find the vault gone; finish or cancel the handoff first
+    require!(vault_state.pending_admin.is_none(), CustomError::HandoffInProgress);
+    let amount = vault.lamports();
+    let beneficiary_key = beneficiary.key();
+    let cpi_program = system_program.to_account_info();
+    let cpi_accounts = Transfer {
+        from: vault.to_account_info(),
+        to: beneficiary,
+    };
+    let seeds = &[
+        b"vault",
+        vault_state.to_account_info().key.as_ref(),
+        &[vault_state.vault_bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+    transfer(cpi_ctx, amount)?;
+    emit!(VaultClosedEvent {
+        vault_state: vault_state.key(),
+        beneficiary: beneficiary_key,
+        amount,
+    });
+    Ok(())
 }
\ No newline at end of file
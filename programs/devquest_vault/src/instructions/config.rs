@@ -0,0 +1,172 @@
+// src/instructions/config.rs
+// Vault configuration instruction implementation
+
+use anchor_lang::prelude::*;
+use crate::{errors::CustomError, state::{EpochSpending, VaultState, MAX_DEPOSITORS}, utils::current_timestamp};
+
+/// Accounts required for updating vault-level configuration
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"state", vault_state.creator.as_ref(), VaultState::nonce_seed(vault_state.vault_nonce).as_ref()],
+        bump = vault_state.state_bump,
+        constraint = user.key() == vault_state.admin @ CustomError::UnauthorizedAdmin,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+}
+
+pub fn set_restricted_deposits(ctx: Context<UpdateConfig>, restricted: bool) -> Result<()> {
+    ctx.accounts.set_restricted_deposits(restricted)
+}
+
+pub fn add_depositor(ctx: Context<UpdateConfig>, depositor: Pubkey) -> Result<()> {
+    ctx.accounts.add_depositor(depositor)
+}
+
+pub fn remove_depositor(ctx: Context<UpdateConfig>, depositor: Pubkey) -> Result<()> {
+    ctx.accounts.remove_depositor(depositor)
+}
+
+pub fn set_max_balance(ctx: Context<UpdateConfig>, max_balance: u64) -> Result<()> {
+    ctx.accounts.set_max_balance(max_balance)
+}
+
+pub fn set_withdrawal_delay(
+    ctx: Context<UpdateConfig>,
+    instant_limit: u64,
+    delay: i64,
+) -> Result<()> {
+    ctx.accounts.set_withdrawal_delay(instant_limit, delay)
+}
+
+pub fn cancel_withdrawal(ctx: Context<UpdateConfig>) -> Result<()> {
+    ctx.accounts.cancel_withdrawal()
+}
+
+pub fn set_paused(ctx: Context<UpdateConfig>, paused: bool) -> Result<()> {
+    ctx.accounts.set_paused(paused)
+}
+
+pub fn set_global_limit(ctx: Context<UpdateConfig>, limit: u64, duration: i64) -> Result<()> {
+    ctx.accounts.set_global_limit(limit, duration)
+}
+
+pub fn set_velocity_limit(ctx: Context<UpdateConfig>, limit: u64, window: i64) -> Result<()> {
+    ctx.accounts.set_velocity_limit(limit, window)
+}
+
+pub fn set_track_admin_spending(ctx: Context<UpdateConfig>, track: bool) -> Result<()> {
+    ctx.accounts.set_track_admin_spending(track)
+}
+
+pub fn set_require_withdraw_memo(ctx: Context<UpdateConfig>, required: bool) -> Result<()> {
+    ctx.accounts.set_require_withdraw_memo(required)
+}
+
+pub fn set_min_interval(ctx: Context<UpdateConfig>, min_interval: i64) -> Result<()> {
+    ctx.accounts.set_min_interval(min_interval)
+}
+
+impl<'info> UpdateConfig<'info> {
+    /// Handler for toggling whitelist-only deposits (admin only)
+    pub fn set_restricted_deposits(&mut self, restricted: bool) -> Result<()> {
+        self.vault_state.restricted_deposits = restricted;
+        Ok(())
+    }
+
+    /// Handler for whitelisting a depositor (admin only)
+    pub fn add_depositor(&mut self, depositor: Pubkey) -> Result<()> {
+        require!(self.vault_state.depositors.len() < MAX_DEPOSITORS, CustomError::MaxDepositorsReached);
+        require!(!self.vault_state.depositors.contains(&depositor), CustomError::DepositorAlreadyExists);
+        self.vault_state.depositors.push(depositor);
+        Ok(())
+    }
+
+    /// Handler for removing a whitelisted depositor (admin only)
+    pub fn remove_depositor(&mut self, depositor: Pubkey) -> Result<()> {
+        if let Some(index) = self.vault_state.depositors.iter().position(|x| *x == depositor) {
+            self.vault_state.depositors.remove(index);
+            Ok(())
+        } else {
+            err!(CustomError::DepositorNotFound)
+        }
+    }
+
+    /// Handler for capping the vault's usable balance, 0 for unlimited (admin only)
+    pub fn set_max_balance(&mut self, max_balance: u64) -> Result<()> {
+        self.vault_state.max_balance = max_balance;
+        Ok(())
+    }
+
+    /// Handler for requiring a delay on withdrawals above `instant_limit`, 0 delay to disable (admin only)
+    pub fn set_withdrawal_delay(&mut self, instant_limit: u64, delay: i64) -> Result<()> {
+        require!(delay >= 0, CustomError::InvalidWithdrawalDelay);
+        self.vault_state.instant_limit = instant_limit;
+        self.vault_state.withdrawal_delay = delay;
+        Ok(())
+    }
+
+    /// Handler for aborting a pending withdrawal request (admin only)
+    pub fn cancel_withdrawal(&mut self) -> Result<()> {
+        require!(self.vault_state.pending_withdrawal.is_some(), CustomError::NoPendingWithdrawal);
+        self.vault_state.pending_withdrawal = None;
+        Ok(())
+    }
+
+    /// Handler for halting or resuming withdrawals and claims (admin only)
+    pub fn set_paused(&mut self, paused: bool) -> Result<()> {
+        self.vault_state.is_paused = paused;
+        // Resuming after a tripped circuit breaker starts the outflow count afresh
+        if !paused {
+            self.vault_state.velocity_outflow = 0;
+            self.vault_state.velocity_updated_at = current_timestamp()?;
+        }
+        Ok(())
+    }
+
+    /// Handler for setting the shortest payout interval new or updated schedules may use (admin only)
+    pub fn set_min_interval(&mut self, min_interval: i64) -> Result<()> {
+        require!(min_interval > 0, CustomError::InvalidPayoutSchedule);
+        self.vault_state.min_interval = min_interval;
+        Ok(())
+    }
+
+    /// Handler for counting admin withdrawals in the withdrawal totals (admin only)
+    pub fn set_track_admin_spending(&mut self, track: bool) -> Result<()> {
+        self.vault_state.track_admin_spending = track;
+        Ok(())
+    }
+
+    /// Handler for requiring a memo on every withdrawal (admin only)
+    pub fn set_require_withdraw_memo(&mut self, required: bool) -> Result<()> {
+        self.vault_state.require_withdraw_memo = required;
+        Ok(())
+    }
+
+    /// Handler for capping tracked withdrawals across all users per epoch, 0 to disable (admin only)
+    pub fn set_global_limit(&mut self, limit: u64, duration: i64) -> Result<()> {
+        require!(limit == 0 || duration > 0, CustomError::InvalidEpochConfig);
+        self.vault_state.global_epoch = EpochSpending {
+            epoch_start: current_timestamp()?,
+            limit,
+            duration,
+            ..Default::default()
+        };
+        Ok(())
+    }
+
+    /// Handler for pausing the vault automatically once more than `limit` is paid out
+    /// within `window` seconds, 0 to disable (admin only)
+    pub fn set_velocity_limit(&mut self, limit: u64, window: i64) -> Result<()> {
+        require!(limit == 0 || window > 0, CustomError::InvalidEpochConfig);
+        let state = &mut self.vault_state;
+        state.velocity_limit = limit;
+        state.velocity_window = window;
+        state.velocity_updated_at = current_timestamp()?;
+        state.velocity_outflow = 0;
+        Ok(())
+    }
+}
\ No newline at end of file
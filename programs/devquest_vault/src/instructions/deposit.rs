@@ -2,7 +2,7 @@
 // Deposit instruction implementation
 
 use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
-use crate::state::VaultState;
+use crate::{errors::CustomError, state::VaultState, utils::usable_balance};
 
 /// Accounts required for depositing SOL into the vault
 #[derive(Accounts)]
@@ -16,7 +16,7 @@ pub struct Deposit<'info> {
     )]
     pub vault: SystemAccount<'info>,
     #[account(
-        seeds = [b"state", vault_state.admin.key().as_ref()],
+        seeds = [b"state", vault_state.creator.as_ref(), VaultState::nonce_seed(vault_state.vault_nonce).as_ref()],
         bump = vault_state.state_bump,
     )]
     pub vault_state: Account<'info, VaultState>,
@@ -30,6 +30,16 @@ pub fn handler(ctx: Context<Deposit>, amount: u64) -> Result<()> {
 impl<'info> Deposit<'info> {
     /// Handler for deposit logic
     pub fn deposit(&mut self, amount: u64) -> Result<()> {
+        // When deposits are restricted, only the admin and whitelisted depositors may fund the vault
+        if self.vault_state.restricted_deposits {
+            let user_key = self.user.key();
+            require!(
+                user_key == self.vault_state.admin || self.vault_state.depositors.contains(&user_key),
+                CustomError::UnauthorizedDepositor
+            );
+        }
+        // The cap applies to the usable balance, not the rent-exempt lamports
+        self.vault_state.check_deposit_cap(usable_balance(&self.vault)?, amount)?;
         // Transfer lamports from user to vault
         let cpi_program = self.system_program.to_account_info();
         let cpi_accounts = Transfer {
@@ -0,0 +1,84 @@
+// src/instructions/escheat.rs
+// Escheat instruction implementation
+
+use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+use crate::{errors::CustomError, events::PayoutEscheatedEvent, instructions::withdraw::record_outflow, state::VaultState, utils::{current_timestamp, usable_balance}};
+
+/// Accounts required for redirecting an unclaimed payout to its fallback recipient
+#[derive(Accounts)]
+pub struct Escheat<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    /// The schedule's fallback recipient
+    #[account(mut)]
+    pub fallback_account: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", vault_state.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"state", vault_state.creator.as_ref(), VaultState::nonce_seed(vault_state.vault_nonce).as_ref()],
+        bump = vault_state.state_bump,
+        constraint = user.key() == vault_state.admin @ CustomError::UnauthorizedAdmin,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn escheat(ctx: Context<Escheat>, payee: Pubkey) -> Result<()> {
+    ctx.accounts.escheat(payee)
+}
+
+impl<'info> Escheat<'info> {
+    /// Handler for paying a payout left unclaimed past its grace period to the fallback recipient (admin only)
+    pub fn escheat(&mut self, payee: Pubkey) -> Result<()> {
+        require!(!self.vault_state.is_paused, CustomError::VaultPaused);
+        let current_time = current_timestamp()?;
+        let schedule_index = self.vault_state
+            .active_schedule_index(&payee, current_time)
+            .ok_or(error!(CustomError::ScheduleNotFound))?;
+        let schedule = &self.vault_state.payout_schedules[schedule_index];
+        let recipient = schedule.fallback_recipient
+            .ok_or(error!(CustomError::NoFallbackRecipient))?;
+        require!(self.fallback_account.key() == recipient, CustomError::InvalidBeneficiary);
+        require!(current_time >= schedule.cliff_time, CustomError::CliffNotReached);
+        require!(
+            current_time >= schedule.next_payout_time.saturating_add(schedule.grace_period),
+            CustomError::GracePeriodNotElapsed
+        );
+        // Redirect exactly what the payee could have claimed
+        let (amount, periods) = schedule.claim_amount(current_time, usable_balance(&self.vault)?)?;
+        // Redirected payouts still leave the vault, so they count against the vault-wide cap
+        self.vault_state.charge_global_limit(amount, current_time)?;
+
+        let cpi_program = self.system_program.to_account_info();
+        let cpi_accounts = Transfer {
+            from: self.vault.to_account_info(),
+            to: self.fallback_account.to_account_info(),
+        };
+        let vault_state_key = self.vault_state.key();
+        let seeds = &[
+            b"vault",
+            vault_state_key.as_ref(),
+            &[self.vault_state.vault_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        transfer(cpi_ctx, amount)?;
+        record_outflow(&mut self.vault_state, amount, current_time);
+
+        // The schedule keeps running; the redirected periods are no longer owed to the payee
+        let schedule = &mut self.vault_state.payout_schedules[schedule_index];
+        schedule.mark_claimed(periods, 0);
+        emit!(PayoutEscheatedEvent {
+            payee,
+            recipient,
+            amount,
+            next_payout_time: schedule.next_payout_time,
+        });
+        Ok(())
+    }
+}
\ No newline at end of file
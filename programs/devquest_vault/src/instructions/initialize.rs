@@ -2,10 +2,11 @@
 // Initialize instruction implementation
 
 use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
-use crate::{errors::CustomError, state::VaultState};
+use crate::{errors::CustomError, events::VaultInitializedEvent, utils::is_valid_name, state::{VaultCounter, VaultState, CURRENT_VERSION, DEFAULT_MAX_PAYEES, DEFAULT_MAX_SCHEDULES, DEFAULT_MIN_INTERVAL}};
 
 /// Accounts required for initializing the vault
 #[derive(Accounts)]
+#[instruction(name: [u8; 32], vault_nonce: u64)]
 pub struct Initialize<'info> {
     /// The admin initializing the vault
     #[account(mut)]
@@ -14,11 +15,58 @@ pub struct Initialize<'info> {
     #[account(
         init,
         payer = user,
-        seeds = [b"state", user.key().as_ref()], 
+        seeds = [b"state", user.key().as_ref(), VaultState::nonce_seed(vault_nonce).as_ref()],
         bump,
         space = VaultState::INIT_SPACE,
     )]
     pub vault_state: Account<'info, VaultState>,
+    /// The admin's nonce counter (PDA), created with their first vault
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [b"counter", user.key().as_ref()],
+        bump,
+        space = VaultCounter::INIT_SPACE,
+    )]
+    pub counter: Account<'info, VaultCounter>,
+    /// The vault account (PDA)
+    #[account(
+        mut,
+        seeds = [b"vault", vault_state.key().as_ref()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts required for initializing a vault on behalf of another admin
+#[derive(Accounts)]
+#[instruction(name: [u8; 32], vault_nonce: u64)]
+pub struct InitializeWithAdmin<'info> {
+    /// The account paying for the vault setup
+    #[account(mut)]
+    pub user: Signer<'info>,
+    /// The admin the vault is created for. It must co-sign, or anyone could occupy the
+    /// admin's vault address with a vault configured to their liking.
+    pub admin: Signer<'info>,
+    /// The vault state account (PDA), derived from the admin it is created for
+    #[account(
+        init,
+        payer = user,
+        seeds = [b"state", admin.key().as_ref(), VaultState::nonce_seed(vault_nonce).as_ref()],
+        bump,
+        space = VaultState::INIT_SPACE,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    /// The admin's nonce counter (PDA), created with their first vault
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [b"counter", admin.key().as_ref()],
+        bump,
+        space = VaultCounter::INIT_SPACE,
+    )]
+    pub counter: Account<'info, VaultCounter>,
     /// The vault account (PDA)
     #[account(
         mut,
@@ -29,30 +77,125 @@ pub struct Initialize<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn handler(ctx: Context<Initialize>) -> Result<()> {
-    ctx.accounts.initialize(&ctx.bumps)
+pub fn handler(ctx: Context<Initialize>, name: [u8; 32], vault_nonce: u64) -> Result<()> {
+    ctx.accounts.initialize(name, vault_nonce, &ctx.bumps)
+}
+
+pub fn initialize_with_admin(
+    ctx: Context<InitializeWithAdmin>,
+    name: [u8; 32],
+    vault_nonce: u64,
+) -> Result<()> {
+    ctx.accounts.initialize_with_admin(name, vault_nonce, &ctx.bumps)
 }
 
 impl<'info> Initialize<'info> {
     /// Handler for vault initialization logic
-    pub fn initialize(&mut self, bumps: &InitializeBumps) -> Result<()> {
-        require!(!self.vault_state.is_initialized, CustomError::AlreadyInitialized);
-        // Calculate rent-exempt minimum for the vault
-        let rent_exempt = Rent::get()?.minimum_balance(self.vault.to_account_info().data_len());
-        // Transfer rent-exempt lamports from user to vault
-        let cpi_program = self.system_program.to_account_info();
+    pub fn initialize(&mut self, name: [u8; 32], vault_nonce: u64, bumps: &InitializeBumps) -> Result<()> {
+        // `init` on the state PDA rejects a live vault; the counter rejects a closed one
+        let admin = self.user.key();
+        init_vault(
+            &self.user,
+            &self.vault,
+            &mut self.vault_state,
+            &mut self.counter,
+            &self.system_program,
+            admin,
+            name,
+            vault_nonce,
+            bumps.vault,
+            bumps.vault_state,
+            bumps.counter,
+        )
+    }  
+}
+
+impl<'info> InitializeWithAdmin<'info> {
+    /// Handler for initializing a vault administered by the co-signing `admin`
+    pub fn initialize_with_admin(
+        &mut self,
+        name: [u8; 32],
+        vault_nonce: u64,
+        bumps: &InitializeWithAdminBumps,
+    ) -> Result<()> {
+        let admin = self.admin.key();
+        init_vault(
+            &self.user,
+            &self.vault,
+            &mut self.vault_state,
+            &mut self.counter,
+            &self.system_program,
+            admin,
+            name,
+            vault_nonce,
+            bumps.vault,
+            bumps.vault_state,
+            bumps.counter,
+        )
+    }
+}
+
+/// Funds the vault PDA up to rent exemption and fills in the fresh vault state
+#[allow(clippy::too_many_arguments)]
+fn init_vault<'info>(
+    payer: &Signer<'info>,
+    vault: &SystemAccount<'info>,
+    vault_state: &mut Account<'info, VaultState>,
+    counter: &mut Account<'info, VaultCounter>,
+    system_program: &Program<'info, System>,
+    admin: Pubkey,
+    name: [u8; 32],
+    vault_nonce: u64,
+    vault_bump: u8,
+    state_bump: u8,
+    counter_bump: u8,
+) -> Result<()> {
+    require!(is_valid_name(&name), CustomError::InvalidVaultName);
+    // Nonces only move forward, so a closed vault's address cannot be taken over by a
+    // fresh vault that old payees, delegates, or integrations would mistake for it
+    require!(vault_nonce >= counter.next_nonce, CustomError::StaleVaultNonce);
+    // u64::MAX would leave no nonce to advance to, so it is never handed out
+    counter.next_nonce = vault_nonce
+        .checked_add(1)
+        .ok_or(error!(CustomError::StaleVaultNonce))?;
+    counter.bump = counter_bump;
+    // Calculate rent-exempt minimum for the vault
+    let rent_exempt = Rent::get()?.minimum_balance(vault.to_account_info().data_len());
+    // The vault PDA is derivable by anyone, so it may already hold lamports sent
+    // before initialization. Those lamports carry no state and simply become vault
+    // funds under the admin's control; only top up whatever is missing for rent.
+    let pre_funded = vault.lamports();
+    if pre_funded > 0 {
+        msg!("Vault PDA was pre-funded with {} lamports", pre_funded);
+    }
+    let top_up = rent_exempt.saturating_sub(pre_funded);
+    if top_up > 0 {
+        // Transfer rent-exempt lamports from payer to vault
+        let cpi_program = system_program.to_account_info();
         let cpi_accounts = Transfer {
-            from: self.user.to_account_info(),
-            to: self.vault.to_account_info(),
+            from: payer.to_account_info(),
+            to: vault.to_account_info(),
         };
         let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        transfer(cpi_ctx, rent_exempt)?;
-        // Initialize vault state fields
-        self.vault_state.vault_bump = bumps.vault;
-        self.vault_state.state_bump = bumps.vault_state;
-        self.vault_state.admin = self.user.key();
-        self.vault_state.payees = Vec::new();
-        self.vault_state.is_initialized = true;
-        Ok(())
-    }  
+        transfer(cpi_ctx, top_up)?;
+    }
+    // Initialize vault state fields
+    vault_state.vault_bump = vault_bump;
+    vault_state.state_bump = state_bump;
+    vault_state.creator = admin;
+    vault_state.admin = admin;
+    vault_state.payees = Vec::new();
+    vault_state.max_payees = DEFAULT_MAX_PAYEES;
+    vault_state.max_schedules = DEFAULT_MAX_SCHEDULES;
+    vault_state.version = CURRENT_VERSION;
+    vault_state.min_interval = DEFAULT_MIN_INTERVAL;
+    vault_state.name = name;
+    vault_state.vault_nonce = vault_nonce;
+    emit!(VaultInitializedEvent {
+        vault_state: vault_state.key(),
+        admin,
+        name,
+        vault_nonce,
+    });
+    Ok(())
 }
\ No newline at end of file
@@ -6,10 +6,30 @@ pub mod deposit;
 pub mod withdraw;
 pub mod close;
 pub mod payee;
+pub mod query;
+pub mod config;
+pub mod skim;
+pub mod admin;
+pub mod migrate;
+pub mod wsol;
+pub mod escheat;
+pub mod rebalance;
+pub mod setup;
+pub mod snapshot;
 
 // Re-export account structures
 pub use initialize::*;
 pub use deposit::*;
 pub use withdraw::*;
 pub use payee::*;
-pub use close::*;
\ No newline at end of file
+pub use close::*;
+pub use query::*;
+pub use config::*;
+pub use skim::*;
+pub use admin::*;
+pub use migrate::*;
+pub use wsol::*;
+pub use escheat::*;
+pub use rebalance::*;
+pub use setup::*;
+pub use snapshot::*;
\ No newline at end of file
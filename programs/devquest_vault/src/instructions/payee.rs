@@ -2,7 +2,19 @@
 // Payee management instruction implementation
 
 use anchor_lang::prelude::*;
-use crate::{errors::CustomError, state::{VaultState, EpochSpending, PayoutSchedule}};
+use crate::{errors::CustomError, events::{PayeeAddedEvent, PayeeRemovedEvent, PayoutCancelledEvent, PayoutReassignedEvent, PayoutScheduledEvent, PayoutUpdatedEvent, StreamCancelledEvent}, state::{Authority, VaultState, EpochSpending, PayeeDelegate, PayeeEpochLimit, PayeeLabel, PayoutKind, PayoutSchedule, PayoutSplit, BPS_DENOMINATOR, MAX_EPOCH_LIMITS, MAX_SPLITS}, utils::current_timestamp};
+
+/// Accounts required for settings a payee may manage themselves
+#[derive(Accounts)]
+pub struct ManagePayee<'info> {
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"state", vault_state.creator.as_ref(), VaultState::nonce_seed(vault_state.vault_nonce).as_ref()],
+        bump = vault_state.state_bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+}
 
 /// Accounts required for updating payees and payout schedules
 #[derive(Accounts)]
@@ -11,15 +23,15 @@ pub struct UpdatePayee<'info> {
     pub user: Signer<'info>,
     #[account(
         mut,
-        seeds = [b"state", vault_state.admin.key().as_ref()],
+        seeds = [b"state", vault_state.creator.as_ref(), VaultState::nonce_seed(vault_state.vault_nonce).as_ref()],
         bump = vault_state.state_bump,
         constraint = user.key() == vault_state.admin @ CustomError::UnauthorizedAdmin,
     )]
     pub vault_state: Account<'info, VaultState>,
 }
 
-pub fn add_payee(ctx: Context<UpdatePayee>, payee: Pubkey) -> Result<()> {
-    ctx.accounts.add_payee(payee)
+pub fn add_payee(ctx: Context<UpdatePayee>, payee: Pubkey, label: [u8; 32]) -> Result<()> {
+    ctx.accounts.add_payee(payee, label)
 }
 
 pub fn remove_payee(ctx: Context<UpdatePayee>, payee: Pubkey) -> Result<()> {
@@ -29,6 +41,7 @@ pub fn remove_payee(ctx: Context<UpdatePayee>, payee: Pubkey) -> Result<()> {
 pub fn set_epoch_limit(
     ctx: Context<UpdatePayee>,
     payee: Pubkey,
+    mint: Option<Pubkey>,
     limit: u64,
     duration: i64
 ) -> Result<()> {
@@ -40,29 +53,83 @@ pub fn set_epoch_limit(
     require!(ctx.accounts.user.key() == state.admin, CustomError::UnauthorizedAdmin);
     // Check if payee exists
     require!(state.payees.contains(&payee), CustomError::PayeeNotFound);
-    let now = Clock::get()?.unix_timestamp;
-    // Find existing epoch limit or create new one
-    if let Some(index) = state.epoch_limits.iter().position(|(p, _)| p == &payee) {
-        state.epoch_limits[index].1 = EpochSpending {
+    let now = current_timestamp()?;
+    // Find existing epoch limit for this mint or create new one
+    if let Some(index) = state.epoch_limits.iter().position(|e| e.payee == payee && e.mint == mint) {
+        // Keep the carryover settings, but start the new epoch without a carried amount
+        let existing = &state.epoch_limits[index].spending;
+        state.epoch_limits[index].spending = EpochSpending {
             epoch_start: now,
             spent_amount: 0,
             limit,
             duration,
+            carryover: existing.carryover,
+            max_carryover: existing.max_carryover,
+            carried: 0,
+            self_reset_allowed: existing.self_reset_allowed,
         };
     } else {
-        state.epoch_limits.push((
+        require!(state.epoch_limits.len() < MAX_EPOCH_LIMITS, CustomError::InvalidEpochConfig);
+        state.epoch_limits.push(PayeeEpochLimit {
             payee,
-            EpochSpending {
+            mint,
+            spending: EpochSpending {
                 epoch_start: now,
                 spent_amount: 0,
                 limit,
                 duration,
-            }
-        ));
+                ..Default::default()
+            },
+        });
     }
     Ok(())
 }
 
+pub fn set_epoch_carryover(
+    ctx: Context<UpdatePayee>,
+    payee: Pubkey,
+    mint: Option<Pubkey>,
+    carryover: bool,
+    max_carryover: u64,
+) -> Result<()> {
+    let state = &mut ctx.accounts.vault_state;
+    // Carryover only applies on top of an existing epoch limit
+    let spending = state
+        .epoch_limit_mut(&payee, mint)
+        .ok_or(error!(CustomError::InvalidEpochConfig))?;
+    spending.carryover = carryover;
+    spending.max_carryover = max_carryover;
+    if !carryover {
+        spending.carried = 0;
+    }
+    Ok(())
+}
+
+pub fn set_epoch_self_reset(
+    ctx: Context<UpdatePayee>,
+    payee: Pubkey,
+    mint: Option<Pubkey>,
+    allowed: bool,
+) -> Result<()> {
+    let spending = ctx.accounts.vault_state
+        .epoch_limit_mut(&payee, mint)
+        .ok_or(error!(CustomError::InvalidEpochConfig))?;
+    spending.self_reset_allowed = allowed;
+    Ok(())
+}
+
+pub fn self_reset_epoch(ctx: Context<ManagePayee>) -> Result<()> {
+    ctx.accounts.self_reset_epoch()
+}
+
+pub fn set_claim_delegate(
+    ctx: Context<ManagePayee>,
+    payee: Pubkey,
+    delegate: Option<Pubkey>,
+) -> Result<()> {
+    ctx.accounts.set_claim_delegate(payee, delegate)
+}
+
 pub fn schedule_payout(
     ctx: Context<UpdatePayee>,
     payee: Pubkey,
@@ -73,6 +140,93 @@ pub fn schedule_payout(
     ctx.accounts.schedule_payout(payee, amount, start_time, interval)
 }
 
+pub fn schedule_percent_payout(
+    ctx: Context<UpdatePayee>,
+    payee: Pubkey,
+    bps: u16,
+    start_time: i64,
+    interval: i64,
+) -> Result<()> {
+    ctx.accounts.schedule_percent_payout(payee, bps, start_time, interval)
+}
+
+pub fn schedule_cliff_payout(
+    ctx: Context<UpdatePayee>,
+    payee: Pubkey,
+    amount: u64,
+    cliff_time: i64,
+    interval: i64,
+) -> Result<()> {
+    ctx.accounts.schedule_cliff_payout(payee, amount, cliff_time, interval)
+}
+
+pub fn schedule_stream(
+    ctx: Context<UpdatePayee>,
+    payee: Pubkey,
+    total_amount: u64,
+    start_time: i64,
+    end_time: i64,
+) -> Result<()> {
+    ctx.accounts.schedule_stream(payee, total_amount, start_time, end_time)
+}
+
+pub fn schedule_split_payout(
+    ctx: Context<UpdatePayee>,
+    amount: u64,
+    start_time: i64,
+    interval: i64,
+    splits: Vec<PayoutSplit>,
+) -> Result<()> {
+    ctx.accounts.schedule_split_payout(amount, start_time, interval, splits)
+}
+
+pub fn update_payout(
+    ctx: Context<UpdatePayee>,
+    payee: Pubkey,
+    kind: PayoutKind,
+    interval: i64,
+) -> Result<()> {
+    ctx.accounts.update_payout(payee, kind, interval)
+}
+
+pub fn reassign_payout(
+    ctx: Context<UpdatePayee>,
+    from_payee: Pubkey,
+    to_payee: Pubkey,
+) -> Result<()> {
+    ctx.accounts.reassign_payout(from_payee, to_payee)
+}
+
+pub fn set_payout_fallback(
+    ctx: Context<UpdatePayee>,
+    payee: Pubkey,
+    fallback_recipient: Option<Pubkey>,
+    grace_period: i64,
+) -> Result<()> {
+    ctx.accounts.set_payout_fallback(payee, fallback_recipient, grace_period)
+}
+
+pub fn set_max_per_claim(
+    ctx: Context<UpdatePayee>,
+    payee: Pubkey,
+    max_per_claim: u64,
+) -> Result<()> {
+    ctx.accounts.set_max_per_claim(payee, max_per_claim)
+}
+
+pub fn set_payout_token(
+    ctx: Context<UpdatePayee>,
+    payee: Pubkey,
+    token_mint: Option<Pubkey>,
+    token_amount: u64,
+) -> Result<()> {
+    ctx.accounts.set_payout_token(payee, token_mint, token_amount)
+}
+
+pub fn prune_schedules(ctx: Context<UpdatePayee>) -> Result<u16> {
+    ctx.accounts.prune_schedules()
+}
+
 pub fn cancel_payout(
     ctx: Context<UpdatePayee>,
     payee: Pubkey,
@@ -80,26 +234,34 @@ pub fn cancel_payout(
     ctx.accounts.cancel_payout(payee)
 }
 
+pub fn cancel_split_payout(
+    ctx: Context<UpdatePayee>,
+    schedule_index: u16,
+) -> Result<()> {
+    ctx.accounts.cancel_split_payout(schedule_index)
+}
+
 impl<'info> UpdatePayee<'info> {
-    /// Handler for adding a new payee (admin only)
-    pub fn add_payee(&mut self, payee: Pubkey) -> Result<()> {
-        require!(self.vault_state.payees.len() < 5, CustomError::MaxPayeesReached);
-        require!(!self.vault_state.payees.contains(&payee), CustomError::PayeeAlreadyExists);
-        self.vault_state.payees.push(payee);
-        Ok(())
+    /// Handler for adding a new payee with an optional label (admin only)
+    pub fn add_payee(&mut self, payee: Pubkey, label: [u8; 32]) -> Result<()> {
+        insert_payee(&mut self.vault_state, payee, label)
     }
 
     /// Handler for removing a payee (admin only)
     pub fn remove_payee(&mut self, payee: Pubkey) -> Result<()> {
         if let Some(index) = self.vault_state.payees.iter().position(|x| *x == payee) {
             self.vault_state.payees.remove(index);
-            // Also remove any associated payout schedule
-            let schedule_index = self.vault_state.payout_schedules
-                .iter()
-                .position(|s| s.is_active);
-            if let Some(idx) = schedule_index {
-                self.vault_state.payout_schedules.remove(idx);
-            }
+            // Also remove any associated payout schedules, including splits paying them
+            self.vault_state.payout_schedules.retain(|s| {
+                s.payee != payee && !s.splits.iter().any(|split| split.recipient == payee)
+            });
+            let label = self.vault_state.payee_label(&payee);
+            self.vault_state.payee_labels.retain(|l| l.payee != payee);
+            self.vault_state.payee_activity.retain(|a| a.payee != payee);
+            self.vault_state.payee_delegates.retain(|d| d.payee != payee);
+            // Free the payee's limit slots, so a payee added again starts without spending
+            self.vault_state.epoch_limits.retain(|l| l.payee != payee);
+            emit!(PayeeRemovedEvent { payee, label });
             Ok(())
         } else {
             err!(CustomError::PayeeNotFound)
@@ -114,30 +276,356 @@ impl<'info> UpdatePayee<'info> {
         start_time: i64,
         interval: i64,
     ) -> Result<()> {
-        // Validate inputs
         require!(amount > 0, CustomError::InvalidPayoutSchedule);
-        require!(interval > 0, CustomError::InvalidPayoutSchedule);
-        require!(start_time > Clock::get()?.unix_timestamp, CustomError::InvalidPayoutSchedule);
-        require!(self.vault_state.payees.contains(&payee), CustomError::PayeeNotFound);
-        require!(self.vault_state.payout_schedules.len() < 5, CustomError::MaxSchedulesReached);
-        let schedule = PayoutSchedule {
-            amount,
+        self.push_schedule(PayoutSchedule {
+            payee,
+            kind: PayoutKind::Fixed(amount),
             next_payout_time: start_time,
             interval,
             is_active: true,
+            ..Default::default()
+        })
+    }
+
+    /// Handler for scheduling a payout of a percentage of the vault balance (admin only)
+    pub fn schedule_percent_payout(
+        &mut self,
+        payee: Pubkey,
+        bps: u16,
+        start_time: i64,
+        interval: i64,
+    ) -> Result<()> {
+        let kind = PayoutKind::PercentBps(bps);
+        require!(kind.is_valid(), CustomError::InvalidPayoutSchedule);
+        self.push_schedule(PayoutSchedule {
+            payee,
+            kind,
+            next_payout_time: start_time,
+            interval,
+            is_active: true,
+            ..Default::default()
+        })
+    }
+
+    /// Handler for scheduling a vesting payout with a cliff (admin only)
+    pub fn schedule_cliff_payout(
+        &mut self,
+        payee: Pubkey,
+        amount: u64,
+        cliff_time: i64,
+        interval: i64,
+    ) -> Result<()> {
+        require!(amount > 0, CustomError::InvalidPayoutSchedule);
+        require!(
+            interval > 0 && interval >= self.vault_state.min_interval,
+            CustomError::InvalidPayoutSchedule
+        );
+        let now = current_timestamp()?;
+        require!(cliff_time > now, CustomError::InvalidPayoutSchedule);
+        // Periods accrue from creation, but nothing is claimable before the cliff
+        self.push_schedule(PayoutSchedule {
+            payee,
+            kind: PayoutKind::Fixed(amount),
+            next_payout_time: now + interval,
+            interval,
+            is_active: true,
+            cliff_time,
+            ..Default::default()
+        })
+    }
+
+    /// Handler for scheduling a linear stream that vests `total_amount` between two times (admin only)
+    pub fn schedule_stream(
+        &mut self,
+        payee: Pubkey,
+        total_amount: u64,
+        start_time: i64,
+        end_time: i64,
+    ) -> Result<()> {
+        let kind = PayoutKind::Stream {
+            total_amount,
+            start_time,
+            end_time,
+            claimed_so_far: 0,
         };
-        self.vault_state.payout_schedules.push(schedule);
+        require!(kind.is_valid(), CustomError::InvalidPayoutSchedule);
+        // The stream's duration stands in for the interval, so it is held to the same floor
+        self.push_schedule(PayoutSchedule {
+            payee,
+            kind,
+            next_payout_time: start_time,
+            interval: end_time - start_time,
+            is_active: true,
+            ..Default::default()
+        })
+    }
+
+    /// Handler for scheduling a payout split between several payees by weight (admin only)
+    pub fn schedule_split_payout(
+        &mut self,
+        amount: u64,
+        start_time: i64,
+        interval: i64,
+        splits: Vec<PayoutSplit>,
+    ) -> Result<()> {
+        require!(amount > 0, CustomError::InvalidPayoutSchedule);
+        require!(!splits.is_empty() && splits.len() <= MAX_SPLITS, CustomError::InvalidSplit);
+        // Weights must cover exactly 100% so every period pays out the full amount
+        let total_bps: u64 = splits.iter().map(|s| s.weight_bps as u64).sum();
+        require!(total_bps == BPS_DENOMINATOR, CustomError::InvalidSplit);
+        for (i, split) in splits.iter().enumerate() {
+            require!(split.weight_bps > 0, CustomError::InvalidSplit);
+            require!(
+                !splits[..i].iter().any(|s| s.recipient == split.recipient),
+                CustomError::InvalidSplit
+            );
+            require!(self.vault_state.payees.contains(&split.recipient), CustomError::PayeeNotFound);
+        }
+        // Split schedules are not owned by a single payee and are claimed by index
+        self.push_schedule(PayoutSchedule {
+            payee: Pubkey::default(),
+            kind: PayoutKind::Fixed(amount),
+            next_payout_time: start_time,
+            interval,
+            is_active: true,
+            splits,
+            ..Default::default()
+        })
+    }
+
+    /// Validates the shared schedule parameters and stores the new schedule
+    fn push_schedule(&mut self, schedule: PayoutSchedule) -> Result<()> {
+        insert_schedule(&mut self.vault_state, schedule)
+    }
+
+    /// Handler for changing the amount and interval of a payee's schedule (admin only)
+    pub fn update_payout(&mut self, payee: Pubkey, kind: PayoutKind, interval: i64) -> Result<()> {
+        require!(
+            interval > 0 && interval >= self.vault_state.min_interval,
+            CustomError::InvalidPayoutSchedule
+        );
+        require!(kind.is_valid(), CustomError::InvalidPayoutSchedule);
+        let now = current_timestamp()?;
+        let schedule_index = self.vault_state
+            .active_schedule_index(&payee, now)
+            .ok_or(error!(CustomError::ScheduleNotFound))?;
+        // A stream's terms are fixed once it is running
+        require!(
+            !kind.is_stream() && !self.vault_state.payout_schedules[schedule_index].kind.is_stream(),
+            CustomError::InvalidPayoutSchedule
+        );
+        let schedule = &mut self.vault_state.payout_schedules[schedule_index];
+        // Fixed periods that are already due stay owed at the old amount; the new rate
+        // and interval only apply from the next period on. Percentage schedules are a
+        // live share of the balance, so a due period is paid at the new rate.
+        if let PayoutKind::Fixed(amount) = schedule.kind {
+            let periods = schedule.periods_due(now);
+            if periods > 0 {
+                schedule.accrued = schedule.accrued.saturating_add(amount.saturating_mul(periods));
+                schedule.next_payout_time += schedule.interval * periods as i64;
+            }
+        }
+        schedule.kind = kind;
+        schedule.interval = interval;
+        emit!(PayoutUpdatedEvent {
+            payee,
+            kind: schedule.kind.clone(),
+            interval: schedule.interval,
+            next_payout_time: schedule.next_payout_time,
+        });
+        Ok(())
+    }
+
+    /// Handler for moving a payee's active schedule to another payee, keeping its timeline (admin only)
+    pub fn reassign_payout(&mut self, from_payee: Pubkey, to_payee: Pubkey) -> Result<()> {
+        require!(self.vault_state.payees.contains(&to_payee), CustomError::PayeeNotFound);
+        let now = current_timestamp()?;
+        // A payee can only hold one open schedule
+        require!(
+            !self.vault_state.holds_open_schedule(&to_payee, now),
+            CustomError::InvalidPayoutSchedule
+        );
+        let schedule_index = self.vault_state
+            .active_schedule_index(&from_payee, now)
+            .ok_or(error!(CustomError::ScheduleNotFound))?;
+        let schedule = &mut self.vault_state.payout_schedules[schedule_index];
+        schedule.payee = to_payee;
+        emit!(PayoutReassignedEvent {
+            from_payee,
+            to_payee,
+            next_payout_time: schedule.next_payout_time,
+        });
         Ok(())
     }
 
-    /// Handler for cancelling a payout schedule (admin only)
+    /// Handler for setting where a payee's unclaimed payouts go once the grace period has passed (admin only)
+    pub fn set_payout_fallback(
+        &mut self,
+        payee: Pubkey,
+        fallback_recipient: Option<Pubkey>,
+        grace_period: i64,
+    ) -> Result<()> {
+        require!(grace_period >= 0, CustomError::InvalidPayoutSchedule);
+        let schedule_index = self.vault_state
+            .active_schedule_index(&payee, current_timestamp()?)
+            .ok_or(error!(CustomError::ScheduleNotFound))?;
+        let schedule = &mut self.vault_state.payout_schedules[schedule_index];
+        schedule.fallback_recipient = fallback_recipient;
+        schedule.grace_period = grace_period;
+        Ok(())
+    }
+
+    /// Handler for capping what a single claim on a payee's schedule can pay, 0 for no cap (admin only)
+    pub fn set_max_per_claim(&mut self, payee: Pubkey, max_per_claim: u64) -> Result<()> {
+        let schedule_index = self.vault_state
+            .active_schedule_index(&payee, current_timestamp()?)
+            .ok_or(error!(CustomError::ScheduleNotFound))?;
+        self.vault_state.payout_schedules[schedule_index].max_per_claim = max_per_claim;
+        Ok(())
+    }
+
+    /// Handler for making a payee's schedule also pay `token_amount` of `token_mint` each
+    /// period, or SOL only again with `None` (admin only)
+    pub fn set_payout_token(
+        &mut self,
+        payee: Pubkey,
+        token_mint: Option<Pubkey>,
+        token_amount: u64,
+    ) -> Result<()> {
+        let schedule_index = self.vault_state
+            .active_schedule_index(&payee, current_timestamp()?)
+            .ok_or(error!(CustomError::ScheduleNotFound))?;
+        let schedule = &mut self.vault_state.payout_schedules[schedule_index];
+        // Streams vest lamports continuously and have no periods to pay tokens for
+        require!(!schedule.kind.is_stream(), CustomError::InvalidPayoutSchedule);
+        require!(token_mint.is_none() || token_amount > 0, CustomError::InvalidPayoutSchedule);
+        schedule.token_amount = if token_mint.is_some() { token_amount } else { 0 };
+        schedule.token_mint = token_mint;
+        Ok(())
+    }
+
+    /// Handler for removing every inactive schedule, freeing their slots under
+    /// max_schedules, and returning how many were removed (admin only)
+    pub fn prune_schedules(&mut self) -> Result<u16> {
+        let schedules = &mut self.vault_state.payout_schedules;
+        let before = schedules.len();
+        schedules.retain(|s| s.is_active);
+        Ok((before - schedules.len()) as u16)
+    }
+
+    /// Handler for cancelling a payout schedule (admin only). A stream keeps what has
+    /// vested claimable and stops vesting; any other schedule is deactivated.
     pub fn cancel_payout(&mut self, payee: Pubkey) -> Result<()> {
         require!(self.vault_state.payees.contains(&payee), CustomError::PayeeNotFound);
-        if let Some(schedule_index) = self.vault_state.payout_schedules.iter().position(|_| true) {
-            self.vault_state.payout_schedules[schedule_index].is_active = false;
+        let now = current_timestamp()?;
+        if let Some(schedule_index) = self.vault_state.active_schedule_index(&payee, now) {
+            let schedule = &mut self.vault_state.payout_schedules[schedule_index];
+            if schedule.kind.is_stream() {
+                let (settled, refunded) = schedule.cancel_stream(now);
+                emit!(StreamCancelledEvent { payee, settled, refunded });
+            } else {
+                schedule.is_active = false;
+            }
+            emit!(PayoutCancelledEvent {
+                payee,
+                kind: schedule.kind.clone(),
+                interval: schedule.interval,
+                next_payout_time: schedule.next_payout_time,
+            });
             Ok(())
         } else {
             err!(CustomError::ScheduleNotFound)
         }
     }
+
+    /// Handler for cancelling the split schedule at `schedule_index` (admin only)
+    pub fn cancel_split_payout(&mut self, schedule_index: u16) -> Result<()> {
+        let split_index = self.vault_state
+            .active_split_index(schedule_index)
+            .ok_or(error!(CustomError::ScheduleNotFound))?;
+        let schedule = &mut self.vault_state.payout_schedules[split_index];
+        schedule.is_active = false;
+        emit!(PayoutCancelledEvent {
+            payee: schedule.payee,
+            kind: schedule.kind.clone(),
+            interval: schedule.interval,
+            next_payout_time: schedule.next_payout_time,
+        });
+        Ok(())
+    }
+}
+
+/// Adds `payee` with an optional label, enforcing the payee cap and rejecting duplicates
+pub(crate) fn insert_payee(state: &mut VaultState, payee: Pubkey, label: [u8; 32]) -> Result<()> {
+    if state.payees.len() >= state.max_payees as usize {
+        // Point the admin at the idlest payee as a candidate for removal
+        if let Some(idle) = state.payees
+            .iter()
+            .min_by_key(|p| state.last_activity(p))
+        {
+            msg!(
+                "Least recently active payee: {} (last active at {})",
+                idle,
+                state.last_activity(idle)
+            );
+        }
+        return err!(CustomError::MaxPayeesReached);
+    }
+    require!(!state.payees.contains(&payee), CustomError::PayeeAlreadyExists);
+    state.payees.push(payee);
+    // New payees count as active from the moment they are added
+    state.touch_payee(payee, current_timestamp()?);
+    // All-zero labels are the "no label" default and are not stored
+    if label != [0u8; 32] {
+        state.payee_labels.push(PayeeLabel { payee, label });
+    }
+    emit!(PayeeAddedEvent { payee, label });
+    Ok(())
+}
+
+/// Validates the shared schedule parameters and stores the new schedule
+pub(crate) fn insert_schedule(state: &mut VaultState, schedule: PayoutSchedule) -> Result<()> {
+    state.validate_new_schedule(&schedule, current_timestamp()?)?;
+    emit!(PayoutScheduledEvent {
+        payee: schedule.payee,
+        kind: schedule.kind.clone(),
+        interval: schedule.interval,
+        next_payout_time: schedule.next_payout_time,
+    });
+    state.payout_schedules.push(schedule);
+    Ok(())
+}
+
+impl<'info> ManagePayee<'info> {
+    /// Handler for a payee starting a new SOL epoch once the current one has ended (payee only)
+    pub fn self_reset_epoch(&mut self) -> Result<()> {
+        let user_key = self.user.key();
+        require!(
+            self.vault_state.authorize(&user_key) == Authority::Payee,
+            CustomError::UnauthorizedPayee
+        );
+        let now = current_timestamp()?;
+        // Only the signer's own record can be reset
+        self.vault_state
+            .epoch_limit_mut(&user_key, None)
+            .ok_or(error!(CustomError::InvalidEpochConfig))?
+            .self_reset(now)
+    }
+
+    /// Handler for registering or clearing (`None`) the account allowed to trigger a
+    /// payee's claims (the payee or admin)
+    pub fn set_claim_delegate(&mut self, payee: Pubkey, delegate: Option<Pubkey>) -> Result<()> {
+        let user_key = self.user.key();
+        require!(
+            user_key == payee || self.vault_state.authorize(&user_key) == Authority::Admin,
+            CustomError::UnauthorizedPayee
+        );
+        require!(self.vault_state.payees.contains(&payee), CustomError::PayeeNotFound);
+        let state = &mut self.vault_state;
+        state.payee_delegates.retain(|d| d.payee != payee);
+        if let Some(delegate) = delegate {
+            state.payee_delegates.push(PayeeDelegate { payee, delegate });
+        }
+        Ok(())
+    }
 }
\ No newline at end of file
@@ -0,0 +1,188 @@
+// src/instructions/query.rs
+// Read-only instructions returning vault information via return data
+
+use anchor_lang::{prelude::*, Discriminator};
+use crate::{state::{PayoutKind, VaultState}, utils::{current_timestamp, usable_balance}};
+
+/// Accounts required for read-only vault queries
+#[derive(Accounts)]
+pub struct VaultQuery<'info> {
+    #[account(
+        seeds = [b"vault", vault_state.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        seeds = [b"state", vault_state.creator.as_ref(), VaultState::nonce_seed(vault_state.vault_nonce).as_ref()],
+        bump = vault_state.state_bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+}
+
+/// Snapshot of a payee's spending allowance and next scheduled payout
+#[derive(AnchorDeserialize, AnchorSerialize, Clone)]
+pub struct PayeeStatus {
+    pub epoch_limit: u64,                 // Epoch spending limit (0 if none set)
+    pub epoch_spent: u64,                 // Amount spent in the current epoch (0 once it has ended)
+    pub epoch_remaining: u64,             // Amount that can still be withdrawn this epoch
+    pub epoch_resets_at: i64,             // Timestamp at which the current epoch ends (0 if none)
+    pub next_payout_time: Option<i64>,    // Next payout time of the active schedule, if any
+    pub next_payout_amount: Option<u64>,  // Amount the next payout would pay, if any
+}
+
+/// Space accounting for the vault state account
+#[derive(AnchorDeserialize, AnchorSerialize, Clone)]
+pub struct AccountUsage {
+    pub init_space: u64,                  // Space reserved by VaultState::INIT_SPACE, discriminator included
+    pub allocated: u64,                   // Current data length of the account
+    pub used: u64,                        // Serialized length of the current state, discriminator included
+}
+
+/// Terms of an active payout schedule
+#[derive(AnchorDeserialize, AnchorSerialize, Clone)]
+pub struct ActiveSchedule {
+    pub payee: Pubkey,                    // Payee paid by the schedule (default pubkey for splits)
+    pub kind: PayoutKind,                 // Amount paid each period
+    pub next_payout_time: i64,            // Next payout time
+    pub interval: i64,                    // Time between payouts (in seconds)
+}
+
+/// Summary of a vault for dashboards
+#[derive(AnchorDeserialize, AnchorSerialize, Clone)]
+pub struct VaultInfo {
+    pub name: [u8; 32],                   // Vault name, UTF-8 padded with zeroes
+    pub admin: Pubkey,                    // Current admin
+    pub creator: Pubkey,                  // Admin the state PDA was derived from
+    pub version: u8,                      // Layout version of the vault state
+    pub is_paused: bool,                  // Whether withdrawals and claims are halted
+    pub usable_balance: u64,              // Vault lamports above the rent floor
+    pub payee_count: u16,                 // Number of authorized payees
+    pub active_schedule_count: u16,       // Number of active payout schedules
+    pub total_withdrawn: u64,             // All tracked withdrawals
+    pub admin_withdrawn: u64,             // Admin withdrawals made while tracking was on
+}
+
+pub fn get_vault_info(ctx: Context<VaultQuery>) -> Result<VaultInfo> {
+    ctx.accounts.get_vault_info()
+}
+
+pub fn get_account_usage(ctx: Context<VaultQuery>) -> Result<AccountUsage> {
+    ctx.accounts.get_account_usage()
+}
+
+pub fn get_inactive_payees(ctx: Context<VaultQuery>, threshold: i64) -> Result<Vec<Pubkey>> {
+    ctx.accounts.get_inactive_payees(threshold)
+}
+
+pub fn get_next_claim_time(ctx: Context<VaultQuery>, payee: Pubkey) -> Result<Option<i64>> {
+    ctx.accounts.get_next_claim_time(payee)
+}
+
+pub fn list_active_schedules(ctx: Context<VaultQuery>) -> Result<Vec<ActiveSchedule>> {
+    ctx.accounts.list_active_schedules()
+}
+
+pub fn get_payee_status(ctx: Context<VaultQuery>, payee: Pubkey) -> Result<PayeeStatus> {
+    ctx.accounts.get_payee_status(payee)
+}
+
+impl<'info> VaultQuery<'info> {
+    /// Handler for reporting the vault's name, admin, and balances
+    pub fn get_vault_info(&self) -> Result<VaultInfo> {
+        let state = &self.vault_state;
+        Ok(VaultInfo {
+            name: state.name,
+            admin: state.admin,
+            creator: state.creator,
+            version: state.version,
+            is_paused: state.is_paused,
+            usable_balance: usable_balance(&self.vault)?,
+            payee_count: state.payees.len() as u16,
+            active_schedule_count: state.payout_schedules.iter().filter(|s| s.is_active).count() as u16,
+            total_withdrawn: state.total_withdrawn,
+            admin_withdrawn: state.admin_withdrawn,
+        })
+    }
+
+    /// Handler for reporting how much of the vault state account is in use
+    pub fn get_account_usage(&self) -> Result<AccountUsage> {
+        let mut data = Vec::new();
+        self.vault_state.serialize(&mut data)?;
+        Ok(AccountUsage {
+            init_space: VaultState::INIT_SPACE as u64,
+            allocated: self.vault_state.to_account_info().data_len() as u64,
+            used: (VaultState::DISCRIMINATOR.len() + data.len()) as u64,
+        })
+    }
+
+    /// Handler for listing payees that have not withdrawn or claimed for longer than `threshold` seconds
+    pub fn get_inactive_payees(&self, threshold: i64) -> Result<Vec<Pubkey>> {
+        let now = current_timestamp()?;
+        let state = &self.vault_state;
+        Ok(state.payees
+            .iter()
+            .filter(|p| now - state.last_activity(p) > threshold)
+            .copied()
+            .collect())
+    }
+
+    /// Handler for reporting when a payee can next claim, or None without an active schedule
+    pub fn get_next_claim_time(&self, payee: Pubkey) -> Result<Option<i64>> {
+        let now = current_timestamp()?;
+        let state = &self.vault_state;
+        Ok(state
+            .active_schedule_index(&payee, now)
+            .map(|index| state.payout_schedules[index].next_claim_time(now)))
+    }
+
+    /// Handler for listing the terms of every active schedule, in storage order
+    pub fn list_active_schedules(&self) -> Result<Vec<ActiveSchedule>> {
+        Ok(self.vault_state.payout_schedules
+            .iter()
+            .filter(|s| s.is_active)
+            .map(|s| ActiveSchedule {
+                payee: s.payee,
+                kind: s.kind.clone(),
+                next_payout_time: s.next_payout_time,
+                interval: s.interval,
+            })
+            .collect())
+    }
+
+    /// Handler for reporting a payee's SOL epoch allowance and next scheduled payout
+    pub fn get_payee_status(&self, payee: Pubkey) -> Result<PayeeStatus> {
+        let state = &self.vault_state;
+        let now = current_timestamp()?;
+        // Report the effective allowance, as if a pending epoch reset had already happened
+        let (epoch_limit, epoch_spent, epoch_remaining, epoch_resets_at) = state
+            .epoch_limit(&payee, None)
+            .map(|e| (
+                e.limit,
+                e.effective_spent(now),
+                e.effective_remaining(now),
+                e.effective_resets_at(now),
+            ))
+            .unwrap_or((0, 0, 0, 0));
+        let (next_payout_time, next_payout_amount) = match state.active_schedule_index(&payee, now) {
+            Some(index) => {
+                let schedule = &state.payout_schedules[index];
+                // A stream reports what it has vested so far
+                let amount = if schedule.kind.is_stream() {
+                    schedule.kind.stream_claimable(now)
+                } else {
+                    schedule.accrued + schedule.kind.amount_for(usable_balance(&self.vault)?)
+                };
+                (Some(schedule.next_payout_time), Some(amount))
+            }
+            None => (None, None),
+        };
+        Ok(PayeeStatus {
+            epoch_limit,
+            epoch_spent,
+            epoch_remaining,
+            epoch_resets_at,
+            next_payout_time,
+            next_payout_amount,
+        })
+    }
+}
\ No newline at end of file
@@ -0,0 +1,81 @@
+// src/instructions/rebalance.rs
+// Transfer-between-vaults instruction implementation
+
+use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+use crate::{errors::CustomError, events::VaultTransferEvent, instructions::withdraw::record_outflow, state::VaultState, utils::{current_timestamp, usable_balance}};
+
+/// Accounts required for moving funds from one vault to another with the same admin
+#[derive(Accounts)]
+pub struct TransferBetweenVaults<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", source_state.key().as_ref()],
+        bump = source_state.vault_bump,
+    )]
+    pub source_vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"state", source_state.creator.as_ref(), VaultState::nonce_seed(source_state.vault_nonce).as_ref()],
+        bump = source_state.state_bump,
+        constraint = user.key() == source_state.admin @ CustomError::UnauthorizedAdmin,
+    )]
+    pub source_state: Account<'info, VaultState>,
+    #[account(
+        mut,
+        seeds = [b"vault", destination_state.key().as_ref()],
+        bump = destination_state.vault_bump,
+    )]
+    pub destination_vault: SystemAccount<'info>,
+    #[account(
+        seeds = [b"state", destination_state.creator.as_ref(), VaultState::nonce_seed(destination_state.vault_nonce).as_ref()],
+        bump = destination_state.state_bump,
+        constraint = user.key() == destination_state.admin @ CustomError::UnauthorizedAdmin,
+        constraint = destination_state.key() != source_state.key() @ CustomError::SameVaultTransfer,
+    )]
+    pub destination_state: Account<'info, VaultState>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn transfer_between_vaults(ctx: Context<TransferBetweenVaults>, amount: u64) -> Result<()> {
+    ctx.accounts.transfer_between_vaults(amount)
+}
+
+impl<'info> TransferBetweenVaults<'info> {
+    /// Handler for moving lamports from the source vault to the destination vault (admin of both only)
+    pub fn transfer_between_vaults(&mut self, amount: u64) -> Result<()> {
+        require!(!self.source_state.is_paused, CustomError::VaultPaused);
+        // Keep the source's rent floor and the next due amount of every active schedule
+        let source_balance = usable_balance(&self.source_vault)?;
+        let reserve = self.source_state.pending_obligations(source_balance);
+        require!(
+            amount <= source_balance.saturating_sub(reserve),
+            CustomError::InsufficientFunds
+        );
+        // The destination's deposit cap still applies
+        self.destination_state.check_deposit_cap(usable_balance(&self.destination_vault)?, amount)?;
+        let cpi_program = self.system_program.to_account_info();
+        let cpi_accounts = Transfer {
+            from: self.source_vault.to_account_info(),
+            to: self.destination_vault.to_account_info(),
+        };
+        let source_state_key = self.source_state.key();
+        let seeds = &[
+            b"vault",
+            source_state_key.as_ref(),
+            &[self.source_state.vault_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        transfer(cpi_ctx, amount)?;
+        // Funds leaving the source vault count towards its circuit breaker
+        record_outflow(&mut self.source_state, amount, current_timestamp()?);
+        emit!(VaultTransferEvent {
+            source_vault_state: source_state_key,
+            destination_vault_state: self.destination_state.key(),
+            amount,
+        });
+        Ok(())
+    }
+}
\ No newline at end of file
@@ -0,0 +1,87 @@
+// src/instructions/setup.rs
+// Batched deposit-and-schedule setup instruction implementation
+
+use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+use crate::{
+    errors::CustomError,
+    instructions::payee::{insert_payee, insert_schedule},
+    state::{PayoutKind, PayoutSchedule, VaultState},
+    utils::usable_balance,
+};
+
+/// A fixed-amount payout schedule to create as part of `setup`
+#[derive(AnchorDeserialize, AnchorSerialize, Clone)]
+pub struct ScheduleSetup {
+    pub payee: Pubkey,
+    pub amount: u64,
+    pub start_time: i64,
+    pub interval: i64,
+}
+
+/// Accounts required for funding a vault and adding its payees and schedules in one go
+#[derive(Accounts)]
+pub struct Setup<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", vault_state.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"state", vault_state.creator.as_ref(), VaultState::nonce_seed(vault_state.vault_nonce).as_ref()],
+        bump = vault_state.state_bump,
+        constraint = user.key() == vault_state.admin @ CustomError::UnauthorizedAdmin,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<Setup>,
+    deposit_amount: u64,
+    payees: Vec<Pubkey>,
+    schedules: Vec<ScheduleSetup>,
+) -> Result<()> {
+    ctx.accounts.setup(deposit_amount, payees, schedules)
+}
+
+impl<'info> Setup<'info> {
+    /// Handler for depositing, adding payees and scheduling payouts atomically (admin only).
+    /// Any failing step aborts the transaction, so nothing is applied partially.
+    pub fn setup(
+        &mut self,
+        deposit_amount: u64,
+        payees: Vec<Pubkey>,
+        schedules: Vec<ScheduleSetup>,
+    ) -> Result<()> {
+        for payee in payees {
+            insert_payee(&mut self.vault_state, payee, [0u8; 32])?;
+        }
+        for s in schedules {
+            require!(s.amount > 0, CustomError::InvalidPayoutSchedule);
+            insert_schedule(&mut self.vault_state, PayoutSchedule {
+                payee: s.payee,
+                kind: PayoutKind::Fixed(s.amount),
+                next_payout_time: s.start_time,
+                interval: s.interval,
+                is_active: true,
+                ..Default::default()
+            })?;
+        }
+        if deposit_amount > 0 {
+            // Same cap as a regular deposit: it applies to the usable balance
+            self.vault_state.check_deposit_cap(usable_balance(&self.vault)?, deposit_amount)?;
+            let cpi_program = self.system_program.to_account_info();
+            let cpi_accounts = Transfer {
+                from: self.user.to_account_info(),
+                to: self.vault.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+            transfer(cpi_ctx, deposit_amount)?;
+        }
+        Ok(())
+    }
+}
\ No newline at end of file
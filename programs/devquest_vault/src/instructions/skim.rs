@@ -0,0 +1,63 @@
+// src/instructions/skim.rs
+// Skim instruction implementation
+
+use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+use crate::{errors::CustomError, instructions::withdraw::record_outflow, state::VaultState, utils::{current_timestamp, usable_balance}};
+
+/// Accounts required for skimming free funds back to the admin
+#[derive(Accounts)]
+pub struct Skim<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", vault_state.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"state", vault_state.creator.as_ref(), VaultState::nonce_seed(vault_state.vault_nonce).as_ref()],
+        bump = vault_state.state_bump,
+        constraint = user.key() == vault_state.admin @ CustomError::UnauthorizedAdmin,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<Skim>) -> Result<u64> {
+    ctx.accounts.skim()
+}
+
+impl<'info> Skim<'info> {
+    /// Handler for returning funds not owed to any active schedule to the admin
+    pub fn skim(&mut self) -> Result<u64> {
+        // Keep the rent floor and the next due amount of every active schedule
+        let usable_balance = usable_balance(&self.vault)?;
+        let obligations = self.vault_state.pending_obligations(usable_balance);
+        let amount = usable_balance.saturating_sub(obligations);
+        if amount == 0 {
+            return Ok(0);
+        }
+        // A skim is an admin withdrawal, held to the vault-wide cap and counted in the
+        // totals when admin spending is tracked
+        let now = current_timestamp()?;
+        self.vault_state.spend_allowance(&self.user.key(), amount, now)?;
+        self.vault_state.track_withdrawal(true, amount);
+        let cpi_program = self.system_program.to_account_info();
+        let cpi_accounts = Transfer {
+            from: self.vault.to_account_info(),
+            to: self.user.to_account_info(),
+        };
+        let seeds = &[
+            b"vault",
+            self.vault_state.to_account_info().key.as_ref(),
+            &[self.vault_state.vault_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        transfer(cpi_ctx, amount)?;
+        record_outflow(&mut self.vault_state, amount, now);
+        Ok(amount)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,74 @@
+// src/instructions/snapshot.rs
+// Snapshot instruction implementation
+
+use anchor_lang::prelude::*;
+use crate::{
+    errors::CustomError,
+    state::{PayeeSpent, VaultSnapshot, VaultState, MAX_SNAPSHOT_SLOT_AGE},
+    utils::{current_slot, current_timestamp, usable_balance},
+};
+
+/// Accounts required for recording a snapshot of the vault
+#[derive(Accounts)]
+#[instruction(slot: u64)]
+pub struct Snapshot<'info> {
+    /// The admin, paying for the snapshot account
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        seeds = [b"vault", vault_state.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        seeds = [b"state", vault_state.creator.as_ref(), VaultState::nonce_seed(vault_state.vault_nonce).as_ref()],
+        bump = vault_state.state_bump,
+        constraint = user.key() == vault_state.admin @ CustomError::UnauthorizedAdmin,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    /// One snapshot per vault and slot
+    #[account(
+        init,
+        payer = user,
+        seeds = [b"snapshot", vault_state.key().as_ref(), &slot.to_le_bytes()],
+        bump,
+        space = VaultSnapshot::INIT_SPACE,
+    )]
+    pub snapshot: Account<'info, VaultSnapshot>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<Snapshot>, slot: u64) -> Result<()> {
+    ctx.accounts.snapshot(slot, ctx.bumps.snapshot)
+}
+
+impl<'info> Snapshot<'info> {
+    /// Handler for recording the vault balance and payee spending under `slot` (admin only).
+    /// Clients cannot know the slot a transaction lands in, so a recent slot is accepted.
+    pub fn snapshot(&mut self, slot: u64, bump: u8) -> Result<()> {
+        let current = current_slot()?;
+        require!(
+            slot <= current && current - slot <= MAX_SNAPSHOT_SLOT_AGE,
+            CustomError::InvalidSnapshotSlot
+        );
+        let now = current_timestamp()?;
+        let state = &self.vault_state;
+        let payee_spent = state.payees
+            .iter()
+            .map(|payee| PayeeSpent {
+                payee: *payee,
+                spent: state.epoch_limit(payee, None).map_or(0, |e| e.effective_spent(now)),
+            })
+            .collect();
+        self.snapshot.set_inner(VaultSnapshot {
+            vault_state: state.key(),
+            slot,
+            timestamp: now,
+            balance: usable_balance(&self.vault)?,
+            payee_count: state.payees.len() as u16,
+            payee_spent,
+            bump,
+        });
+        Ok(())
+    }
+}
\ No newline at end of file
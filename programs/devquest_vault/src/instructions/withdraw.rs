@@ -2,7 +2,8 @@
 // Withdraw instruction implementation
 
 use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
-use crate::{errors::CustomError, state::VaultState};
+use anchor_spl::token::{self, Token, TokenAccount};
+use crate::{errors::CustomError, events::{CircuitBreakerTripped, ClaimAttempt, PayoutClaimedEvent, WithdrawEvent}, state::{Authority, PayoutKind, PendingWithdrawal, VaultState}, utils::{current_timestamp, usable_balance}};
 
 /// Accounts required for withdrawing SOL from the vault
 #[derive(Accounts)]
@@ -16,54 +17,332 @@ pub struct Withdraw<'info> {
     )]
     pub vault: SystemAccount<'info>,
     #[account(
-        seeds = [b"state", vault_state.admin.key().as_ref()],
+        mut,
+        seeds = [b"state", vault_state.creator.as_ref(), VaultState::nonce_seed(vault_state.vault_nonce).as_ref()],
         bump = vault_state.state_bump,
     )]
     pub vault_state: Account<'info, VaultState>,
     pub system_program: Program<'info, System>,
+    /// Only needed to claim a dual-asset schedule
+    pub token_program: Option<Program<'info, Token>>,
+    /// The vault's token account for the schedule's mint
+    #[account(mut)]
+    pub vault_token_account: Option<Account<'info, TokenAccount>>,
+    /// The payee's token account for the schedule's mint
+    #[account(mut)]
+    pub payee_token_account: Option<Account<'info, TokenAccount>>,
+    /// The payee a delegate claims for; only needed by claim_payout_delegated
+    #[account(mut)]
+    pub payee: Option<SystemAccount<'info>>,
 }
 
 pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
     ctx.accounts.withdraw(amount)
 }
 
+pub fn withdraw_with_memo(ctx: Context<Withdraw>, amount: u64, memo: [u8; 64]) -> Result<()> {
+    ctx.accounts.withdraw_with_memo(amount, memo)
+}
+
+pub fn request_withdrawal(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+    ctx.accounts.request_withdrawal(amount)
+}
+
+pub fn request_withdrawal_with_memo(ctx: Context<Withdraw>, amount: u64, memo: [u8; 64]) -> Result<()> {
+    ctx.accounts.request_withdrawal_with_memo(amount, memo)
+}
+
+pub fn execute_withdrawal(ctx: Context<Withdraw>) -> Result<()> {
+    ctx.accounts.execute_withdrawal()
+}
+
 pub fn claim_payout(ctx: Context<Withdraw>) -> Result<()> {
     ctx.accounts.claim_payout()
 }
 
+pub fn claim_payout_delegated(ctx: Context<Withdraw>) -> Result<()> {
+    ctx.accounts.claim_payout_delegated()
+}
+
+pub fn claim_stream(ctx: Context<Withdraw>) -> Result<()> {
+    ctx.accounts.claim_stream()
+}
+
+pub fn claim_split_payout<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Withdraw<'info>>,
+    schedule_index: u16,
+) -> Result<()> {
+    ctx.accounts.claim_split_payout(schedule_index, ctx.remaining_accounts)
+}
+
 impl<'info> Withdraw<'info> {
     /// Handler for withdrawal logic (admin or authorized payee)
     pub fn withdraw(&mut self, amount: u64) -> Result<()> {
+        require!(!self.vault_state.require_withdraw_memo, CustomError::MemoRequired);
+        // Large withdrawals must go through request_withdrawal when a delay is configured
+        require!(
+            self.vault_state.withdrawal_delay == 0 || amount <= self.vault_state.instant_limit,
+            CustomError::WithdrawalDelayRequired
+        );
+        self.process_withdrawal(amount, [0u8; 64])
+    }
+
+    /// Handler for withdrawing with a memo recorded in the withdraw event (admin or authorized payee)
+    pub fn withdraw_with_memo(&mut self, amount: u64, memo: [u8; 64]) -> Result<()> {
+        // An all-zero memo carries no reason code
+        require!(memo.iter().any(|b| *b != 0), CustomError::MemoRequired);
+        require!(
+            self.vault_state.withdrawal_delay == 0 || amount <= self.vault_state.instant_limit,
+            CustomError::WithdrawalDelayRequired
+        );
+        self.process_withdrawal(amount, memo)
+    }
+
+    /// Handler for requesting a delayed withdrawal (admin or authorized payee)
+    pub fn request_withdrawal(&mut self, amount: u64) -> Result<()> {
+        // A delayed withdrawal must not get around the memo requirement
+        require!(!self.vault_state.require_withdraw_memo, CustomError::MemoRequired);
+        self.queue_withdrawal(amount, [0u8; 64])
+    }
+
+    /// Handler for requesting a delayed withdrawal whose memo is recorded when it is
+    /// executed (admin or authorized payee)
+    pub fn request_withdrawal_with_memo(&mut self, amount: u64, memo: [u8; 64]) -> Result<()> {
+        require!(memo.iter().any(|b| *b != 0), CustomError::MemoRequired);
+        self.queue_withdrawal(amount, memo)
+    }
+
+    /// Stores a withdrawal of `amount` for the signer, executable once the delay has elapsed
+    fn queue_withdrawal(&mut self, amount: u64, memo: [u8; 64]) -> Result<()> {
+        let user_key = self.user.key();
+        let now = current_timestamp()?;
+        self.vault_state.check_withdrawal_request(&user_key, amount, now)?;
+        // The admin can clear a request blocking the slot with cancel_withdrawal
+        require!(self.vault_state.pending_withdrawal.is_none(), CustomError::PendingWithdrawalExists);
+        self.vault_state.pending_withdrawal = Some(PendingWithdrawal {
+            requester: user_key,
+            amount,
+            execute_after: now.saturating_add(self.vault_state.withdrawal_delay),
+            memo,
+        });
+        Ok(())
+    }
+
+    /// Handler for executing a requested withdrawal once its delay has elapsed (requester only)
+    pub fn execute_withdrawal(&mut self) -> Result<()> {
+        let pending = self.vault_state.pending_withdrawal
+            .clone()
+            .ok_or(error!(CustomError::NoPendingWithdrawal))?;
+        require!(pending.requester == self.user.key(), CustomError::UnauthorizedPayee);
+        let now = current_timestamp()?;
+        require!(now >= pending.execute_after, CustomError::WithdrawalDelayNotElapsed);
+        self.vault_state.pending_withdrawal = None;
+        // The requester must still be authorized and within limits when executing
+        self.process_withdrawal(pending.amount, pending.memo)
+    }
+
+    /// Checks authorization and epoch limits, then transfers from the vault to the user
+    fn process_withdrawal(&mut self, amount: u64, memo: [u8; 64]) -> Result<()> {
+        require!(!self.vault_state.is_paused, CustomError::VaultPaused);
         // Check if user is admin or authorized payee
-        if self.user.key() != self.vault_state.admin &&
-           !self.vault_state.payees.contains(&self.user.key()) {
-            return err!(CustomError::UnauthorizedPayee);
+        let authority = self.vault_state.authorize(&self.user.key());
+        require!(authority != Authority::Unauthorized, CustomError::UnauthorizedPayee);
+        let now = current_timestamp()?;
+        let is_admin = authority == Authority::Admin;
+        if !is_admin {
+            self.vault_state.touch_payee(self.user.key(), now);
+        }
+        self.vault_state.spend_allowance(&self.user.key(), amount, now)?;
+        // Fail with a clear error rather than letting the system program reject the transfer
+        require!(usable_balance(&self.vault)? >= amount, CustomError::InsufficientFunds);
+        // Admin withdrawals only count towards the totals when tracking is on
+        let state = &mut self.vault_state;
+        state.track_withdrawal(is_admin, amount);
+        emit!(WithdrawEvent {
+            user: self.user.key(),
+            amount,
+            is_admin,
+            total_withdrawn: state.total_withdrawn,
+            memo,
+        });
+        // Perform the withdrawal from vault to user
+        self.transfer_from_vault(self.user.to_account_info(), amount)?;
+        record_outflow(&mut self.vault_state, amount, now);
+        Ok(())
+    }
+
+    /// Handler for claiming a scheduled payout (payee only)
+    pub fn claim_payout(&mut self) -> Result<()> {
+        let payee = self.user.to_account_info();
+        self.claim_payout_to(payee)
+    }
+
+    /// Handler for claiming a payee's scheduled payout on their behalf, paid to the payee
+    /// (the payee's registered delegate only)
+    pub fn claim_payout_delegated(&mut self) -> Result<()> {
+        let payee = self.payee
+            .as_ref()
+            .ok_or(error!(CustomError::PayeeNotFound))?
+            .to_account_info();
+        require!(
+            self.vault_state.claim_delegate(payee.key) == Some(self.user.key()),
+            CustomError::UnauthorizedDelegate
+        );
+        self.claim_payout_to(payee)
+    }
+
+    /// Pays `payee` their due scheduled payout, checking authorization against the payee
+    fn claim_payout_to(&mut self, payee: AccountInfo<'info>) -> Result<()> {
+        require!(!self.vault_state.is_paused, CustomError::VaultPaused);
+        let user_key = payee.key();
+        require!(
+            self.vault_state.authorize(&user_key) == Authority::Payee,
+            CustomError::UnauthorizedPayee
+        );
+        let current_time = current_timestamp()?;
+        // Find the payee's active payout schedule
+        let schedule_index = self.vault_state
+            .active_schedule_index(&user_key, current_time)
+            .ok_or(error!(CustomError::NoScheduleForPayee))?;
+        // Vesting schedules release nothing before their cliff
+        let schedule = &self.vault_state.payout_schedules[schedule_index];
+        require!(current_time >= schedule.cliff_time, CustomError::CliffNotReached);
+        // Check if it's time for payout, logging the near-miss for keepers
+        if !schedule.is_due(current_time) {
+            emit!(ClaimAttempt {
+                payee: user_key,
+                next_payout_time: schedule.next_payout_time,
+                attempted_at: current_time,
+            });
+            return err!(CustomError::PayoutTimeNotReached);
+        }
+        // Percentage schedules are computed on the balance above the rent floor, so
+        // even a 100% payout leaves the vault rent-exempt
+        // Every period elapsed since the last claim is paid at once (e.g. the lump sum at a cliff)
+        let (due, periods) = schedule.claim_amount(current_time, usable_balance(&self.vault)?)?;
+        // A large catch-up is drawn over several claims when the schedule caps each one
+        let amount = schedule.capped(due);
+        // Dual-asset schedules also pay their token amount for every period covered
+        let token_mint = schedule.token_mint;
+        let token_amount = schedule.token_amount.saturating_mul(periods);
+        // Scheduled claims count against the payee's epoch limit and the vault-wide cap
+        // just like withdrawals
+        self.vault_state.charge_payee_limit(&user_key, amount, current_time)?;
+        self.vault_state.charge_global_limit(amount, current_time)?;
+        if let Some(mint) = token_mint.filter(|_| token_amount > 0) {
+            // The token leg counts against the payee's limit for that mint
+            self.vault_state.charge_payee_mint_limit(&user_key, Some(mint), token_amount, current_time)?;
+            self.transfer_tokens_from_vault(mint, token_amount, &user_key)?;
+        }
+        // Transfer the scheduled amount from vault to the payee
+        self.transfer_from_vault(payee, amount)?;
+        // Advance the next payout time past every period covered, keeping the rest owed
+        self.vault_state.payout_schedules[schedule_index].mark_claimed(periods, due - amount);
+        self.vault_state.touch_payee(user_key, current_time);
+        record_outflow(&mut self.vault_state, amount, current_time);
+        emit!(PayoutClaimedEvent {
+            payee: user_key,
+            amount,
+            deferred: due - amount,
+            token_amount,
+        });
+        Ok(())
+    }
+
+    /// Handler for claiming the newly vested part of a stream (payee only)
+    pub fn claim_stream(&mut self) -> Result<()> {
+        require!(!self.vault_state.is_paused, CustomError::VaultPaused);
+        let user_key = self.user.key();
+        require!(
+            self.vault_state.authorize(&user_key) == Authority::Payee,
+            CustomError::UnauthorizedPayee
+        );
+        let current_time = current_timestamp()?;
+        let schedule_index = self.vault_state
+            .active_stream_index(&user_key, current_time)
+            .ok_or(error!(CustomError::NoScheduleForPayee))?;
+        let schedule = &self.vault_state.payout_schedules[schedule_index];
+        require!(schedule.kind.is_stream(), CustomError::InvalidPayoutSchedule);
+        // Pay the delta vested since the last claim
+        let vested = schedule.kind.stream_claimable(current_time);
+        require!(vested > 0, CustomError::PayoutTimeNotReached);
+        let amount = schedule.capped(vested);
+        require!(usable_balance(&self.vault)? >= amount, CustomError::InsufficientFunds);
+        self.vault_state.charge_payee_limit(&user_key, amount, current_time)?;
+        self.vault_state.charge_global_limit(amount, current_time)?;
+        self.transfer_from_vault(self.user.to_account_info(), amount)?;
+        let schedule = &mut self.vault_state.payout_schedules[schedule_index];
+        if let PayoutKind::Stream { total_amount, claimed_so_far, .. } = &mut schedule.kind {
+            *claimed_so_far += amount;
+            // A fully claimed stream is finished
+            if *claimed_so_far >= *total_amount {
+                schedule.is_active = false;
+            }
+        }
+        self.vault_state.touch_payee(user_key, current_time);
+        record_outflow(&mut self.vault_state, amount, current_time);
+        emit!(PayoutClaimedEvent {
+            payee: user_key,
+            amount,
+            deferred: vested - amount,
+            token_amount: 0,
+        });
+        Ok(())
+    }
+
+    /// Handler for paying out a split schedule to its recipients, passed as remaining
+    /// accounts in the order of the schedule's splits (anyone may crank)
+    pub fn claim_split_payout(
+        &mut self,
+        schedule_index: u16,
+        recipients: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        require!(!self.vault_state.is_paused, CustomError::VaultPaused);
+        let current_time = current_timestamp()?;
+        let split_index = self.vault_state
+            .active_split_index(schedule_index)
+            .ok_or(error!(CustomError::ScheduleNotFound))?;
+        let schedule = &self.vault_state.payout_schedules[split_index];
+        // Recipients must line up exactly with the stored splits
+        require!(recipients.len() == schedule.splits.len(), CustomError::InvalidSplit);
+        require!(
+            recipients.iter().zip(&schedule.splits).all(|(a, s)| a.key() == s.recipient),
+            CustomError::InvalidSplit
+        );
+        require!(current_time >= schedule.cliff_time, CustomError::CliffNotReached);
+        require!(current_time >= schedule.next_payout_time, CustomError::PayoutTimeNotReached);
+        let (periods, per_period) = schedule.payable_periods(current_time, usable_balance(&self.vault)?);
+        require!(periods > 0, CustomError::InsufficientFunds);
+        let total = per_period.saturating_mul(periods);
+        let shares = schedule.split_shares(total);
+        self.vault_state.charge_global_limit(total, current_time)?;
+        // Each recipient's share counts against their own epoch limit, as a direct claim would
+        for (recipient, share) in recipients.iter().zip(&shares) {
+            self.vault_state.charge_payee_limit(recipient.key, *share, current_time)?;
         }
-        // If user is not admin, check epoch spending limits
-        if self.user.key() != self.vault_state.admin {
-            let now = Clock::get()?.unix_timestamp;
-            if let Some((_, epoch_spending)) = self.vault_state.epoch_limits
-                .iter_mut()
-                .find(|(p, _)| p == &self.user.key())
-            {
-                // Reset epoch if needed
-                if now >= epoch_spending.epoch_start + epoch_spending.duration {
-                    epoch_spending.epoch_start = now;
-                    epoch_spending.spent_amount = 0;
-                }
-                // Check if withdrawal exceeds limit
-                if epoch_spending.spent_amount + amount > epoch_spending.limit {
-                    return err!(CustomError::EpochSpendingLimitReached);
-                }
-                // Update spent amount
-                epoch_spending.spent_amount += amount;
+        for (recipient, share) in recipients.iter().zip(shares) {
+            if share > 0 {
+                self.transfer_from_vault(recipient.clone(), share)?;
             }
         }
-        // Perform the withdrawal from vault to user
+        // Being paid by a split counts as activity for recipients who are payees
+        for recipient in recipients {
+            if self.vault_state.payees.contains(recipient.key) {
+                self.vault_state.touch_payee(recipient.key(), current_time);
+            }
+        }
+        let schedule = &mut self.vault_state.payout_schedules[split_index];
+        schedule.next_payout_time += schedule.interval * periods as i64;
+        record_outflow(&mut self.vault_state, total, current_time);
+        Ok(())
+    }
+
+    /// Transfers `amount` from the vault PDA to `to`, signing with the vault seeds
+    fn transfer_from_vault(&self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
         let cpi_program = self.system_program.to_account_info();
         let cpi_accounts = Transfer {
             from: self.vault.to_account_info(),
-            to: self.user.to_account_info(),
+            to,
         };
         let vault_state_key = self.vault_state.to_account_info().key;
         let vault_bump = self.vault_state.vault_bump;
@@ -74,29 +353,28 @@ impl<'info> Withdraw<'info> {
         ];
         let signer_seeds = &[&seeds[..]];
         let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
-        transfer(cpi_ctx, amount)?;
-        Ok(())
+        transfer(cpi_ctx, amount)
     }
 
-    /// Handler for claiming a scheduled payout (payee only)
-    pub fn claim_payout(&mut self) -> Result<()> {
-        let user_key = self.user.key();
-        require!(self.vault_state.payees.contains(&user_key), CustomError::UnauthorizedPayee);
-        let current_time = Clock::get()?.unix_timestamp;
-        // Find the active payout schedule
-        let schedule_index = self.vault_state.payout_schedules
-            .iter()
-            .position(|s| s.is_active)
-            .ok_or(error!(CustomError::ScheduleNotFound))?;
-        // Check if it's time for payout
-        let schedule = &self.vault_state.payout_schedules[schedule_index];
-        require!(current_time >= schedule.next_payout_time, CustomError::PayoutTimeNotReached);
-        let amount = schedule.amount;
-        // Transfer the scheduled amount from vault to user
-        let cpi_program = self.system_program.to_account_info();
-        let cpi_accounts = Transfer {
-            from: self.vault.to_account_info(),
-            to: self.user.to_account_info(),
+    /// Transfers `amount` of `mint` from the vault's token account to the payee's, signing
+    /// with the vault seeds. Both accounts must hold `mint`, and belong to the vault and
+    /// `recipient` respectively.
+    fn transfer_tokens_from_vault(&self, mint: Pubkey, amount: u64, recipient: &Pubkey) -> Result<()> {
+        let (Some(token_program), Some(from), Some(to)) = (
+            &self.token_program,
+            &self.vault_token_account,
+            &self.payee_token_account,
+        ) else {
+            return err!(CustomError::InvalidTokenAccount);
+        };
+        require!(from.mint == mint && from.owner == self.vault.key(), CustomError::InvalidTokenAccount);
+        require!(to.mint == mint && to.owner == *recipient, CustomError::InvalidTokenAccount);
+        // Token accounts have no rent floor to keep, but must cover the whole amount
+        require!(from.amount >= amount, CustomError::InsufficientFunds);
+        let cpi_accounts = token::Transfer {
+            from: from.to_account_info(),
+            to: to.to_account_info(),
+            authority: self.vault.to_account_info(),
         };
         let vault_state_key = self.vault_state.to_account_info().key;
         let vault_bump = self.vault_state.vault_bump;
@@ -106,11 +384,20 @@ impl<'info> Withdraw<'info> {
             &[vault_bump],
         ];
         let signer_seeds = &[&seeds[..]];
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
-        transfer(cpi_ctx, amount)?;
-        // Update next payout time for the schedule
-        self.vault_state.payout_schedules[schedule_index].next_payout_time += 
-            self.vault_state.payout_schedules[schedule_index].interval;
-        Ok(())
+        let cpi_ctx = CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer_seeds);
+        token::transfer(cpi_ctx, amount)
+    }
+}
+
+/// Feeds `amount` of SOL paid out of the vault into the circuit breaker, announcing it
+/// if the vault pauses. Every instruction moving SOL out of a vault PDA calls this,
+/// except closing, which drains the vault and deletes its state.
+pub(crate) fn record_outflow(state: &mut VaultState, amount: u64, now: i64) {
+    if state.record_outflow(amount, now) {
+        emit!(CircuitBreakerTripped {
+            outflow: state.velocity_outflow,
+            limit: state.velocity_limit,
+            window: state.velocity_window,
+        });
     }
 }
\ No newline at end of file
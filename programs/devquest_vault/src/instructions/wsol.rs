@@ -0,0 +1,136 @@
+// src/instructions/wsol.rs
+// Wrapped SOL deposit and unwrap instruction implementation
+
+use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{close_account, spl_token::native_mint, sync_native, CloseAccount, Mint, SyncNative, Token, TokenAccount},
+};
+use crate::{errors::CustomError, state::VaultState, utils::usable_balance};
+
+/// Accounts required for depositing SOL as wrapped SOL into the vault's token account
+#[derive(Accounts)]
+pub struct DepositAndWrap<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        seeds = [b"vault", vault_state.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"state", vault_state.creator.as_ref(), VaultState::nonce_seed(vault_state.vault_nonce).as_ref()],
+        bump = vault_state.state_bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(address = native_mint::ID)]
+    pub native_mint: Account<'info, Mint>,
+    /// The vault's wSOL associated token account, created on first use
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = native_mint,
+        associated_token::authority = vault,
+    )]
+    pub vault_wsol: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts required for unwrapping the vault's wSOL back into native SOL
+#[derive(Accounts)]
+pub struct UnwrapWsol<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", vault_state.key().as_ref()],
+        bump = vault_state.vault_bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"state", vault_state.creator.as_ref(), VaultState::nonce_seed(vault_state.vault_nonce).as_ref()],
+        bump = vault_state.state_bump,
+        constraint = user.key() == vault_state.admin @ CustomError::UnauthorizedAdmin,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(address = native_mint::ID)]
+    pub native_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = native_mint,
+        associated_token::authority = vault,
+    )]
+    pub vault_wsol: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn deposit_and_wrap(ctx: Context<DepositAndWrap>, amount: u64) -> Result<()> {
+    ctx.accounts.deposit_and_wrap(amount)
+}
+
+pub fn unwrap_wsol(ctx: Context<UnwrapWsol>) -> Result<()> {
+    ctx.accounts.unwrap_wsol()
+}
+
+impl<'info> DepositAndWrap<'info> {
+    /// Handler for depositing SOL into the vault's wSOL token account
+    pub fn deposit_and_wrap(&mut self, amount: u64) -> Result<()> {
+        // Wrapped deposits follow the same whitelist as native deposits
+        if self.vault_state.restricted_deposits {
+            let user_key = self.user.key();
+            require!(
+                user_key == self.vault_state.admin || self.vault_state.depositors.contains(&user_key),
+                CustomError::UnauthorizedDepositor
+            );
+        }
+        // Wrapped SOL is still vault funds, so the cap covers the native and wrapped
+        // holdings together; the token amount already excludes the account's rent
+        let holdings = usable_balance(&self.vault)?.saturating_add(self.vault_wsol.amount);
+        self.vault_state.check_deposit_cap(holdings, amount)?;
+        // Transfer lamports from user straight into the native token account
+        let cpi_program = self.system_program.to_account_info();
+        let cpi_accounts = Transfer {
+            from: self.user.to_account_info(),
+            to: self.vault_wsol.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        transfer(cpi_ctx, amount)?;
+        // Sync the token balance with the lamports just added
+        let cpi_ctx = CpiContext::new(
+            self.token_program.to_account_info(),
+            SyncNative { account: self.vault_wsol.to_account_info() },
+        );
+        sync_native(cpi_ctx)?;
+        self.vault_state.wsol_enabled = true;
+        Ok(())
+    }
+}
+
+impl<'info> UnwrapWsol<'info> {
+    /// Handler for closing the vault's wSOL account into the vault as native SOL (admin only)
+    pub fn unwrap_wsol(&mut self) -> Result<()> {
+        let seeds = &[
+            b"vault",
+            self.vault_state.to_account_info().key.as_ref(),
+            &[self.vault_state.vault_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+        let cpi_accounts = CloseAccount {
+            account: self.vault_wsol.to_account_info(),
+            destination: self.vault.to_account_info(),
+            authority: self.vault.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
+        );
+        close_account(cpi_ctx)?;
+        self.vault_state.wsol_enabled = false;
+        Ok(())
+    }
+}
\ No newline at end of file
@@ -1,15 +1,22 @@
 // src/lib.rs
 // Main program entry point and module declarations
 
+// Each instruction module exposes its own `handler`, and the program module
+// re-declares the instruction names, so the glob re-exports overlap by design
+#![allow(ambiguous_glob_reexports)]
+
 use anchor_lang::prelude::*;
 
 // Import our modules
 mod errors;
+mod events;
 mod state;
 mod instructions;
+mod utils;
 
 // Re-export what we want to be public
 pub use errors::*;
+pub use events::*;
 pub use state::*;
 pub use instructions::*;
 
@@ -22,14 +29,38 @@ declare_id!("cDTxPaqGFcfNwMmcj283j7dNPrYdMfw3J1T2oYzv36U");
 pub mod devquest_vault {
     use super::*;
 
-    /// Initializes the vault and vault state accounts
-    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
-        instructions::initialize::handler(ctx)
+    /// Initializes the vault and vault state accounts with a display name and a nonce distinguishing it from the creator's other vaults
+    pub fn initialize(ctx: Context<Initialize>, name: [u8; 32], vault_nonce: u64) -> Result<()> {
+        instructions::initialize::handler(ctx, name, vault_nonce)
+    }
+
+    /// Initializes a vault administered by the co-signing `admin`, paid for by the user
+    pub fn initialize_with_admin(
+        ctx: Context<InitializeWithAdmin>,
+        name: [u8; 32],
+        vault_nonce: u64,
+    ) -> Result<()> {
+        instructions::initialize::initialize_with_admin(ctx, name, vault_nonce)
+    }
+
+    /// Proposes a new admin, who must accept before taking over (admin only)
+    pub fn propose_admin(ctx: Context<UpdateAdmin>, new_admin: Pubkey) -> Result<()> {
+        instructions::admin::propose_admin(ctx, new_admin)
+    }
+
+    /// Withdraws a pending admin proposal (admin only)
+    pub fn cancel_admin_proposal(ctx: Context<UpdateAdmin>) -> Result<()> {
+        instructions::admin::cancel_admin_proposal(ctx)
+    }
+
+    /// Accepts a pending admin handoff (proposed admin only)
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        instructions::admin::accept_admin(ctx)
     }
 
-    /// Adds a new payee to the vault (admin only)
-    pub fn add_payee(ctx: Context<UpdatePayee>, payee: Pubkey) -> Result<()> {
-        instructions::payee::add_payee(ctx, payee)
+    /// Adds a new payee to the vault with an optional 32-byte label (admin only)
+    pub fn add_payee(ctx: Context<UpdatePayee>, payee: Pubkey, label: [u8; 32]) -> Result<()> {
+        instructions::payee::add_payee(ctx, payee, label)
     }
 
     /// Removes a payee from the vault (admin only)
@@ -42,14 +73,56 @@ pub mod devquest_vault {
         instructions::deposit::handler(ctx, amount)
     }
 
-    /// Sets an epoch spending limit for a payee (admin only)
+    /// Deposits SOL into the vault's wrapped SOL token account
+    pub fn deposit_and_wrap(ctx: Context<DepositAndWrap>, amount: u64) -> Result<()> {
+        instructions::wsol::deposit_and_wrap(ctx, amount)
+    }
+
+    /// Unwraps the vault's wrapped SOL back into the vault as native SOL (admin only)
+    pub fn unwrap_wsol(ctx: Context<UnwrapWsol>) -> Result<()> {
+        instructions::wsol::unwrap_wsol(ctx)
+    }
+
+    /// Sets a payee's epoch spending limit for a mint, None for SOL (admin only)
     pub fn set_epoch_limit(
         ctx: Context<UpdatePayee>,
         payee: Pubkey,
+        mint: Option<Pubkey>,
         limit: u64,
         duration: i64
     ) -> Result<()> {
-        instructions::payee::set_epoch_limit(ctx, payee, limit, duration)
+        instructions::payee::set_epoch_limit(ctx, payee, mint, limit, duration)
+    }
+
+    /// Lets a payee's unused epoch allowance roll into the next epoch, up to a cap (admin only)
+    pub fn set_epoch_carryover(
+        ctx: Context<UpdatePayee>,
+        payee: Pubkey,
+        mint: Option<Pubkey>,
+        carryover: bool,
+        max_carryover: u64,
+    ) -> Result<()> {
+        instructions::payee::set_epoch_carryover(ctx, payee, mint, carryover, max_carryover)
+    }
+
+    /// Lets a payee start their next epoch for `mint` themselves once the current one ends (admin only)
+    pub fn set_epoch_self_reset(
+        ctx: Context<UpdatePayee>,
+        payee: Pubkey,
+        mint: Option<Pubkey>,
+        allowed: bool,
+    ) -> Result<()> {
+        instructions::payee::set_epoch_self_reset(ctx, payee, mint, allowed)
+    }
+
+    /// Starts a new SOL epoch for the signing payee once the current one has ended (payee only, if allowed)
+    pub fn self_reset_epoch(ctx: Context<ManagePayee>) -> Result<()> {
+        instructions::payee::self_reset_epoch(ctx)
+    }
+
+    /// Registers or clears the account allowed to trigger a payee's claims (payee or admin)
+    pub fn set_claim_delegate(ctx: Context<ManagePayee>, payee: Pubkey, delegate: Option<Pubkey>) -> Result<()> {
+        instructions::payee::set_claim_delegate(ctx, payee, delegate)
     }
 
     /// Withdraws SOL from the vault (admin or authorized payee)
@@ -57,11 +130,120 @@ pub mod devquest_vault {
         instructions::withdraw::withdraw(ctx, amount)
     }
 
+    /// Withdraws SOL from the vault with a reason code recorded in the event (admin or authorized payee)
+    pub fn withdraw_with_memo(ctx: Context<Withdraw>, amount: u64, memo: [u8; 64]) -> Result<()> {
+        instructions::withdraw::withdraw_with_memo(ctx, amount, memo)
+    }
+
+    /// Requests a withdrawal above the instant limit, executable after the delay (admin or authorized payee)
+    pub fn request_withdrawal(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        instructions::withdraw::request_withdrawal(ctx, amount)
+    }
+
+    /// Requests a delayed withdrawal carrying a reason code for its withdraw event (admin or authorized payee)
+    pub fn request_withdrawal_with_memo(ctx: Context<Withdraw>, amount: u64, memo: [u8; 64]) -> Result<()> {
+        instructions::withdraw::request_withdrawal_with_memo(ctx, amount, memo)
+    }
+
+    /// Executes a requested withdrawal once its delay has elapsed (requester only)
+    pub fn execute_withdrawal(ctx: Context<Withdraw>) -> Result<()> {
+        instructions::withdraw::execute_withdrawal(ctx)
+    }
+
+    /// Aborts a pending withdrawal request (admin only)
+    pub fn cancel_withdrawal(ctx: Context<UpdateConfig>) -> Result<()> {
+        instructions::config::cancel_withdrawal(ctx)
+    }
+
+    /// Requires a delay for withdrawals above `instant_limit`; a zero delay disables it (admin only)
+    pub fn set_withdrawal_delay(
+        ctx: Context<UpdateConfig>,
+        instant_limit: u64,
+        delay: i64,
+    ) -> Result<()> {
+        instructions::config::set_withdrawal_delay(ctx, instant_limit, delay)
+    }
+
     /// Closes the vault and returns remaining funds to the admin
     pub fn close(ctx: Context<Close>) -> Result<()> {
         instructions::close::handler(ctx)
     }
 
+    /// Moves funds from one vault to another administered by the same admin (admin of both only)
+    pub fn transfer_between_vaults(ctx: Context<TransferBetweenVaults>, amount: u64) -> Result<()> {
+        instructions::rebalance::transfer_between_vaults(ctx, amount)
+    }
+
+    /// Deposits into the vault and adds payees and fixed payout schedules in one transaction (admin only)
+    pub fn setup(
+        ctx: Context<Setup>,
+        deposit_amount: u64,
+        payees: Vec<Pubkey>,
+        schedules: Vec<ScheduleSetup>,
+    ) -> Result<()> {
+        instructions::setup::handler(ctx, deposit_amount, payees, schedules)
+    }
+
+    /// Records the vault balance and each payee's epoch spending in a PDA for `slot` (admin only)
+    pub fn snapshot(ctx: Context<Snapshot>, slot: u64) -> Result<()> {
+        instructions::snapshot::handler(ctx, slot)
+    }
+
+    /// Closes the vault, sending its funds to `beneficiary` and the state rent to the admin
+    pub fn close_to(ctx: Context<CloseTo>, beneficiary: Pubkey) -> Result<()> {
+        instructions::close::close_to(ctx, beneficiary)
+    }
+
+    /// Caps the vault's usable balance for deposits, 0 for unlimited (admin only)
+    pub fn set_max_balance(ctx: Context<UpdateConfig>, max_balance: u64) -> Result<()> {
+        instructions::config::set_max_balance(ctx, max_balance)
+    }
+
+    /// Caps total withdrawals across all payees per epoch, 0 to disable (admin only)
+    pub fn set_global_limit(ctx: Context<UpdateConfig>, limit: u64, duration: i64) -> Result<()> {
+        instructions::config::set_global_limit(ctx, limit, duration)
+    }
+
+    /// Pauses the vault automatically once more than `limit` is paid out within `window` seconds, 0 to disable (admin only)
+    pub fn set_velocity_limit(ctx: Context<UpdateConfig>, limit: u64, window: i64) -> Result<()> {
+        instructions::config::set_velocity_limit(ctx, limit, window)
+    }
+
+    /// Counts admin withdrawals in the vault's withdrawal totals (admin only)
+    pub fn set_track_admin_spending(ctx: Context<UpdateConfig>, track: bool) -> Result<()> {
+        instructions::config::set_track_admin_spending(ctx, track)
+    }
+
+    /// Requires withdrawals to carry a memo via withdraw_with_memo (admin only)
+    pub fn set_require_withdraw_memo(ctx: Context<UpdateConfig>, required: bool) -> Result<()> {
+        instructions::config::set_require_withdraw_memo(ctx, required)
+    }
+
+    /// Sets the shortest payout interval schedules may use (admin only)
+    pub fn set_min_interval(ctx: Context<UpdateConfig>, min_interval: i64) -> Result<()> {
+        instructions::config::set_min_interval(ctx, min_interval)
+    }
+
+    /// Halts or resumes withdrawals and claims (admin only)
+    pub fn set_paused(ctx: Context<UpdateConfig>, paused: bool) -> Result<()> {
+        instructions::config::set_paused(ctx, paused)
+    }
+
+    /// Upgrades an older vault state account to the current layout (admin only)
+    pub fn migrate(ctx: Context<Migrate>) -> Result<()> {
+        instructions::migrate::handler(ctx)
+    }
+
+    /// Checks the stored vault and state bumps against the canonical PDA bumps, e.g. after a migration (admin only)
+    pub fn validate_bumps(ctx: Context<ValidateBumps>) -> Result<()> {
+        instructions::migrate::validate_bumps(ctx)
+    }
+
+    /// Withdraws funds not owed to active schedules to the admin (admin only)
+    pub fn skim(ctx: Context<Skim>) -> Result<u64> {
+        instructions::skim::handler(ctx)
+    }
+
     /// Schedules a recurring payout for a payee (admin only)
     pub fn schedule_payout(
         ctx: Context<UpdatePayee>,
@@ -73,6 +255,69 @@ pub mod devquest_vault {
         instructions::payee::schedule_payout(ctx, payee, amount, start_time, interval)
     }
 
+    /// Schedules a recurring payout of a percentage of the vault balance (admin only)
+    pub fn schedule_percent_payout(
+        ctx: Context<UpdatePayee>,
+        payee: Pubkey,
+        bps: u16,
+        start_time: i64,
+        interval: i64,
+    ) -> Result<()> {
+        instructions::payee::schedule_percent_payout(ctx, payee, bps, start_time, interval)
+    }
+
+    /// Schedules a vesting payout that accrues from now but unlocks at a cliff (admin only)
+    pub fn schedule_cliff_payout(
+        ctx: Context<UpdatePayee>,
+        payee: Pubkey,
+        amount: u64,
+        cliff_time: i64,
+        interval: i64,
+    ) -> Result<()> {
+        instructions::payee::schedule_cliff_payout(ctx, payee, amount, cliff_time, interval)
+    }
+
+    /// Changes the amount and interval of a payee's payout schedule (admin only)
+    pub fn update_payout(
+        ctx: Context<UpdatePayee>,
+        payee: Pubkey,
+        kind: PayoutKind,
+        interval: i64,
+    ) -> Result<()> {
+        instructions::payee::update_payout(ctx, payee, kind, interval)
+    }
+
+    /// Schedules a linear stream vesting `total_amount` between two times (admin only)
+    pub fn schedule_stream(
+        ctx: Context<UpdatePayee>,
+        payee: Pubkey,
+        total_amount: u64,
+        start_time: i64,
+        end_time: i64,
+    ) -> Result<()> {
+        instructions::payee::schedule_stream(ctx, payee, total_amount, start_time, end_time)
+    }
+
+    /// Schedules a payout split between several payees by bps weight (admin only)
+    pub fn schedule_split_payout(
+        ctx: Context<UpdatePayee>,
+        amount: u64,
+        start_time: i64,
+        interval: i64,
+        splits: Vec<PayoutSplit>,
+    ) -> Result<()> {
+        instructions::payee::schedule_split_payout(ctx, amount, start_time, interval, splits)
+    }
+
+    /// Moves a payee's active schedule to another existing payee (admin only)
+    pub fn reassign_payout(
+        ctx: Context<UpdatePayee>,
+        from_payee: Pubkey,
+        to_payee: Pubkey,
+    ) -> Result<()> {
+        instructions::payee::reassign_payout(ctx, from_payee, to_payee)
+    }
+
     /// Cancels a payout schedule for a payee (admin only)
     pub fn cancel_payout(
         ctx: Context<UpdatePayee>,
@@ -81,10 +326,113 @@ pub mod devquest_vault {
         instructions::payee::cancel_payout(ctx, payee)
     }
 
+    /// Cancels the split payout schedule at `schedule_index` (admin only)
+    pub fn cancel_split_payout(ctx: Context<UpdatePayee>, schedule_index: u16) -> Result<()> {
+        instructions::payee::cancel_split_payout(ctx, schedule_index)
+    }
+
+    /// Removes inactive payout schedules, returning how many were removed (admin only)
+    pub fn prune_schedules(ctx: Context<UpdatePayee>) -> Result<u16> {
+        instructions::payee::prune_schedules(ctx)
+    }
+
+    /// Sets the fallback recipient of a payee's schedule and its grace period (admin only)
+    pub fn set_payout_fallback(
+        ctx: Context<UpdatePayee>,
+        payee: Pubkey,
+        fallback_recipient: Option<Pubkey>,
+        grace_period: i64,
+    ) -> Result<()> {
+        instructions::payee::set_payout_fallback(ctx, payee, fallback_recipient, grace_period)
+    }
+
+    /// Caps what a single claim on a payee's schedule can pay, 0 for no cap (admin only)
+    pub fn set_max_per_claim(ctx: Context<UpdatePayee>, payee: Pubkey, max_per_claim: u64) -> Result<()> {
+        instructions::payee::set_max_per_claim(ctx, payee, max_per_claim)
+    }
+
+    /// Makes a payee's schedule also pay a token amount each period, or SOL only with `None` (admin only)
+    pub fn set_payout_token(
+        ctx: Context<UpdatePayee>,
+        payee: Pubkey,
+        token_mint: Option<Pubkey>,
+        token_amount: u64,
+    ) -> Result<()> {
+        instructions::payee::set_payout_token(ctx, payee, token_mint, token_amount)
+    }
+
+    /// Redirects a payout unclaimed past its grace period to the fallback recipient (admin only)
+    pub fn escheat(ctx: Context<Escheat>, payee: Pubkey) -> Result<()> {
+        instructions::escheat::escheat(ctx, payee)
+    }
+
+    /// Claims the newly vested part of a stream (payee only)
+    pub fn claim_stream(ctx: Context<Withdraw>) -> Result<()> {
+        instructions::withdraw::claim_stream(ctx)
+    }
+
+    /// Pays a split schedule to its recipients, passed as remaining accounts (anyone may call)
+    pub fn claim_split_payout<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Withdraw<'info>>,
+        schedule_index: u16,
+    ) -> Result<()> {
+        instructions::withdraw::claim_split_payout(ctx, schedule_index)
+    }
+
     /// Allows a payee to claim their scheduled payout
     pub fn claim_payout(
         ctx: Context<Withdraw>,
     ) -> Result<()> {
         instructions::withdraw::claim_payout(ctx)
     }
+
+    /// Claims a payee's scheduled payout on their behalf, paying the payee (the payee's claim delegate only)
+    pub fn claim_payout_delegated(ctx: Context<Withdraw>) -> Result<()> {
+        instructions::withdraw::claim_payout_delegated(ctx)
+    }
+
+    /// Restricts deposits to the admin and whitelisted depositors (admin only)
+    pub fn set_restricted_deposits(ctx: Context<UpdateConfig>, restricted: bool) -> Result<()> {
+        instructions::config::set_restricted_deposits(ctx, restricted)
+    }
+
+    /// Adds a depositor to the deposit whitelist (admin only)
+    pub fn add_depositor(ctx: Context<UpdateConfig>, depositor: Pubkey) -> Result<()> {
+        instructions::config::add_depositor(ctx, depositor)
+    }
+
+    /// Removes a depositor from the deposit whitelist (admin only)
+    pub fn remove_depositor(ctx: Context<UpdateConfig>, depositor: Pubkey) -> Result<()> {
+        instructions::config::remove_depositor(ctx, depositor)
+    }
+
+    /// Returns the vault's name, admin, version, and usable balance
+    pub fn get_vault_info(ctx: Context<VaultQuery>) -> Result<VaultInfo> {
+        instructions::query::get_vault_info(ctx)
+    }
+
+    /// Returns the reserved, allocated, and used byte counts of the vault state account
+    pub fn get_account_usage(ctx: Context<VaultQuery>) -> Result<AccountUsage> {
+        instructions::query::get_account_usage(ctx)
+    }
+
+    /// Returns the payees idle (no withdrawal or claim) for longer than `threshold` seconds
+    pub fn get_inactive_payees(ctx: Context<VaultQuery>, threshold: i64) -> Result<Vec<Pubkey>> {
+        instructions::query::get_inactive_payees(ctx, threshold)
+    }
+
+    /// Returns when a payee can next claim (the current time if already due), or None without a schedule
+    pub fn get_next_claim_time(ctx: Context<VaultQuery>, payee: Pubkey) -> Result<Option<i64>> {
+        instructions::query::get_next_claim_time(ctx, payee)
+    }
+
+    /// Returns the payee, amount, next payout time, and interval of every active schedule
+    pub fn list_active_schedules(ctx: Context<VaultQuery>) -> Result<Vec<ActiveSchedule>> {
+        instructions::query::list_active_schedules(ctx)
+    }
+
+    /// Returns a payee's SOL epoch allowance and next scheduled payout
+    pub fn get_payee_status(ctx: Context<VaultQuery>, payee: Pubkey) -> Result<PayeeStatus> {
+        instructions::query::get_payee_status(ctx, payee)
+    }
 }
\ No newline at end of file
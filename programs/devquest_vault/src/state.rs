@@ -2,14 +2,247 @@
 // State definitions for the vault program
 
 use anchor_lang::prelude::*;
+use crate::errors::CustomError;
+
+/// Default cap on the number of payees, set at initialization
+pub const DEFAULT_MAX_PAYEES: u16 = 5;
+/// Default cap on the number of payout schedules, set at initialization
+pub const DEFAULT_MAX_SCHEDULES: u16 = 5;
+/// Default minimum payout interval in seconds, set at initialization
+pub const DEFAULT_MIN_INTERVAL: i64 = 3600;
+/// Cap on the number of recipients of a split payout schedule
+pub const MAX_SPLITS: usize = 5;
+/// Cap on the number of epoch limit records; a payee may hold one per mint
+pub const MAX_EPOCH_LIMITS: usize = 10;
+/// Cap on the number of whitelisted depositors
+pub const MAX_DEPOSITORS: usize = 5;
+/// How many slots old the slot a snapshot is filed under may be
+pub const MAX_SNAPSHOT_SLOT_AGE: u64 = 150;
+
+/// Current layout version of VaultState; bump together with a migration path.
+/// v2 is the one layout shipped with the version field, including every field added
+/// before that release; any later layout change needs a v3.
+pub const CURRENT_VERSION: u8 = 2;
+
+/// Denominator for basis-point percentages (10000 bps = 100%)
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// How the amount of a scheduled payout is determined each period
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, PartialEq, Eq)]
+pub enum PayoutKind {
+    Fixed(u64),                      // Fixed amount of lamports per period
+    PercentBps(u16),                 // Share of the vault's usable balance, in basis points
+    Stream {                         // Linear vesting of `total_amount` between the two times
+        total_amount: u64,
+        start_time: i64,
+        end_time: i64,
+        claimed_so_far: u64,
+    },
+}
+
+impl Default for PayoutKind {
+    fn default() -> Self {
+        PayoutKind::Fixed(0)
+    }
+}
+
+impl PayoutKind {
+    /// Whether the kind pays something: a non-zero amount, 1..=10000 bps, or a
+    /// non-empty stream over a non-empty time range
+    pub fn is_valid(&self) -> bool {
+        match self {
+            PayoutKind::Fixed(amount) => *amount > 0,
+            PayoutKind::PercentBps(bps) => *bps > 0 && *bps as u64 <= BPS_DENOMINATOR,
+            PayoutKind::Stream { total_amount, start_time, end_time, .. } => {
+                *total_amount > 0 && end_time > start_time
+            }
+        }
+    }
+
+    /// Whether the kind vests continuously rather than per period
+    pub fn is_stream(&self) -> bool {
+        matches!(self, PayoutKind::Stream { .. })
+    }
+
+    /// Amount due for one period, given the vault balance available above the rent floor.
+    /// A stream has no periods, so its whole unclaimed remainder is reported.
+    pub fn amount_for(&self, usable_balance: u64) -> u64 {
+        match self {
+            PayoutKind::Fixed(amount) => *amount,
+            PayoutKind::PercentBps(bps) => {
+                (usable_balance as u128 * *bps as u128 / BPS_DENOMINATOR as u128) as u64
+            }
+            PayoutKind::Stream { total_amount, claimed_so_far, .. } => {
+                total_amount.saturating_sub(*claimed_so_far)
+            }
+        }
+    }
+
+    /// Amount of a stream vested at `now`, clamped to the total after the end time (0 for other kinds)
+    pub fn vested_at(&self, now: i64) -> u64 {
+        match self {
+            PayoutKind::Stream { total_amount, start_time, end_time, .. } => {
+                if now <= *start_time {
+                    0
+                } else if now >= *end_time {
+                    *total_amount
+                } else {
+                    (*total_amount as u128 * (now - start_time) as u128
+                        / (end_time - start_time) as u128) as u64
+                }
+            }
+            _ => 0,
+        }
+    }
+
+    /// Amount of a stream vested at `now` but not yet claimed (0 for other kinds)
+    pub fn stream_claimable(&self, now: i64) -> u64 {
+        match self {
+            PayoutKind::Stream { claimed_so_far, .. } => {
+                self.vested_at(now).saturating_sub(*claimed_so_far)
+            }
+            _ => 0,
+        }
+    }
+}
+
+/// One recipient's share of a split payout schedule
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Default)]
+pub struct PayoutSplit {
+    pub recipient: Pubkey,           // Payee receiving this share
+    pub weight_bps: u16,             // Share of each payout, in basis points
+}
 
 /// Data structure for a scheduled payout
 #[derive(AnchorDeserialize, AnchorSerialize, Clone, Default)]
 pub struct PayoutSchedule {
-    pub amount: u64,                 // Amount to be paid
+    pub payee: Pubkey,               // Payee this schedule pays out to
+    pub kind: PayoutKind,            // Amount to be paid (fixed or percentage of balance)
     pub next_payout_time: i64,       // Timestamp for next payout
     pub interval: i64,               // Time between payouts (in seconds)
     pub is_active: bool,             // Whether this schedule is active
+    pub cliff_time: i64,             // No claims before this timestamp (0 = no cliff)
+    pub splits: Vec<PayoutSplit>,    // Recipients of a split schedule (empty = pays `payee`)
+    pub accrued: u64,                // Owed for periods that came due before a rate change
+    pub fallback_recipient: Option<Pubkey>, // Receives payouts left unclaimed past the grace period
+    pub grace_period: i64,           // Seconds after a due payout before it can be redirected
+    pub max_per_claim: u64,          // Cap on what a single claim pays (0 = no cap)
+    pub token_mint: Option<Pubkey>,  // Mint of the token also paid each period (None = SOL only)
+    pub token_amount: u64,           // Tokens paid per period alongside the lamports
+}
+
+impl PayoutSchedule {
+    /// Number of whole periods due at `now`, counting every interval that has elapsed
+    /// since `next_payout_time`. Percentage schedules are evaluated against the live
+    /// balance, so they are always claimed one period at a time.
+    pub fn periods_due(&self, now: i64) -> u64 {
+        if now < self.next_payout_time {
+            return 0;
+        }
+        match self.kind {
+            PayoutKind::Fixed(_) => ((now - self.next_payout_time) / self.interval) as u64 + 1,
+            PayoutKind::PercentBps(_) => 1,
+            PayoutKind::Stream { .. } => 0,
+        }
+    }
+
+    /// Whole periods due at `now` that `usable_balance` can cover, and the amount paid per period
+    pub fn payable_periods(&self, now: i64, usable_balance: u64) -> (u64, u64) {
+        let periods_due = self.periods_due(now);
+        let per_period = self.kind.amount_for(usable_balance);
+        let periods = match usable_balance.checked_div(per_period) {
+            Some(affordable) => periods_due.min(affordable),
+            None => periods_due,
+        };
+        (periods, per_period)
+    }
+
+    /// Whether a claim at `now` has something to pay: a period has come due or amounts are accrued
+    pub fn is_due(&self, now: i64) -> bool {
+        now >= self.next_payout_time || self.accrued > 0
+    }
+
+    /// Earliest time from `now` on at which a claim can succeed: `now` if something is
+    /// already owed, otherwise the next payout time, and never before the cliff
+    pub fn next_claim_time(&self, now: i64) -> i64 {
+        let due_at = if self.accrued > 0 { now } else { self.next_payout_time };
+        due_at.max(self.cliff_time).max(now)
+    }
+
+    /// Amount a claim at `now` pays and the number of periods it covers. Amounts locked in
+    /// before a rate change are owed first; of the periods due, only the whole periods the
+    /// rest of `usable_balance` can cover are paid, and the others stay claimable later.
+    pub fn claim_amount(&self, now: i64, usable_balance: u64) -> Result<(u64, u64)> {
+        // Streams are paid through claim_stream
+        require!(!self.kind.is_stream(), CustomError::InvalidPayoutSchedule);
+        require!(usable_balance >= self.accrued, CustomError::InsufficientFunds);
+        let (periods, per_period) = self.payable_periods(now, usable_balance - self.accrued);
+        require!(periods > 0 || self.accrued > 0, CustomError::InsufficientFunds);
+        Ok((self.accrued + per_period.saturating_mul(periods), periods))
+    }
+
+    /// Part of `amount` a single claim may pay under `max_per_claim`
+    pub fn capped(&self, amount: u64) -> u64 {
+        if self.max_per_claim > 0 {
+            amount.min(self.max_per_claim)
+        } else {
+            amount
+        }
+    }
+
+    /// Records a claim covering `periods` and advances the next payout time. Whatever the
+    /// claim did not pay (`deferred`) stays accrued, claimable right away.
+    pub fn mark_claimed(&mut self, periods: u64, deferred: u64) {
+        self.accrued = deferred;
+        self.next_payout_time += self.interval * periods as i64;
+    }
+
+    /// Ends a stream at `now`: what has vested stays owed and claimable through
+    /// claim_stream, and the unvested remainder is released back to the vault. A stream
+    /// with nothing left owed is deactivated. Returns the settled (vested but unclaimed)
+    /// and refunded (unvested) amounts; other kinds are left untouched.
+    pub fn cancel_stream(&mut self, now: i64) -> (u64, u64) {
+        let vested = self.kind.vested_at(now);
+        let PayoutKind::Stream { total_amount, end_time, claimed_so_far, .. } = &mut self.kind else {
+            return (0, 0);
+        };
+        let settled = vested.saturating_sub(*claimed_so_far);
+        let refunded = total_amount.saturating_sub(vested);
+        // Truncate the stream so it is fully vested at what was owed
+        *total_amount = vested;
+        *end_time = (*end_time).min(now);
+        if settled == 0 {
+            self.is_active = false;
+        }
+        (settled, refunded)
+    }
+
+    /// Whether this is a stream with nothing left to vest at `now`, because it ended or
+    /// was cancelled. Such a stream only waits for its balance to be claimed.
+    pub fn has_finished_vesting(&self, now: i64) -> bool {
+        matches!(self.kind, PayoutKind::Stream { end_time, .. } if end_time <= now)
+    }
+
+    /// Each split recipient's share of `total`, in the order of `splits`. Rounding dust
+    /// goes to the largest-weight recipient (the first one on a tie), so the shares
+    /// always add up to exactly `total`.
+    pub fn split_shares(&self, total: u64) -> Vec<u64> {
+        let mut shares: Vec<u64> = self.splits
+            .iter()
+            .map(|s| (total as u128 * s.weight_bps as u128 / BPS_DENOMINATOR as u128) as u64)
+            .collect();
+        let distributed: u64 = shares.iter().sum();
+        let largest = self.splits
+            .iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|(_, s)| s.weight_bps)
+            .map(|(i, _)| i);
+        if let Some(i) = largest {
+            shares[i] += total - distributed;
+        }
+        shares
+    }
 }
 
 /// Data structure for tracking epoch-based spending limits
@@ -19,26 +252,1056 @@ pub struct EpochSpending {
     pub spent_amount: u64,           // Amount spent in current epoch
     pub limit: u64,                  // Maximum amount that can be spent in an epoch
     pub duration: i64,               // Duration of epoch in seconds (e.g., 86400 for daily)
+    pub carryover: bool,             // Whether unused allowance rolls into the next epoch
+    pub max_carryover: u64,          // Cap on the allowance carried into an epoch
+    pub carried: u64,                // Allowance carried into the current epoch
+    pub self_reset_allowed: bool,    // Whether the payee may start their next epoch themselves
+}
+
+impl EpochSpending {
+    /// Whether the stored epoch has ended, so its spent amount no longer applies
+    pub fn has_rolled_over(&self, now: i64) -> bool {
+        now >= self.epoch_start.saturating_add(self.duration)
+    }
+
+    /// Amount spent in the epoch that is current at `now` (zero once the stored epoch has ended)
+    pub fn effective_spent(&self, now: i64) -> u64 {
+        if self.has_rolled_over(now) {
+            0
+        } else {
+            self.spent_amount
+        }
+    }
+
+    /// Amount that can still be spent at `now`, accounting for a reset not yet written back
+    pub fn effective_remaining(&self, now: i64) -> u64 {
+        if self.has_rolled_over(now) {
+            self.limit.saturating_add(self.next_carry())
+        } else {
+            self.limit
+                .saturating_add(self.carried)
+                .saturating_sub(self.spent_amount)
+        }
+    }
+
+    /// When the epoch that is current at `now` ends. Once the stored epoch has ended, the
+    /// next spend starts a new one, so this reports the end of an epoch starting at `now`.
+    pub fn effective_resets_at(&self, now: i64) -> i64 {
+        if self.has_rolled_over(now) {
+            now.saturating_add(self.duration)
+        } else {
+            self.epoch_start.saturating_add(self.duration)
+        }
+    }
+
+    /// Allowance the next epoch inherits from this one. Only the unused part of the base
+    /// limit carries over, never the carried amount itself, so it cannot compound.
+    pub fn next_carry(&self) -> u64 {
+        if self.carryover {
+            self.limit.saturating_sub(self.spent_amount).min(self.max_carryover)
+        } else {
+            0
+        }
+    }
+
+    /// Starts a new epoch at `now`, carrying over unused allowance if enabled
+    pub fn roll_over(&mut self, now: i64) {
+        self.carried = self.next_carry();
+        self.epoch_start = now;
+        self.spent_amount = 0;
+    }
+
+    /// Starts a new epoch at `now` on the payee's own request. Only allowed when the admin
+    /// enabled self-resets, and only once the current epoch has genuinely ended.
+    pub fn self_reset(&mut self, now: i64) -> Result<()> {
+        require!(self.self_reset_allowed, CustomError::UnauthorizedPayee);
+        require!(self.duration > 0, CustomError::InvalidEpochConfig);
+        require!(self.has_rolled_over(now), CustomError::EpochNotElapsed);
+        self.roll_over(now);
+        Ok(())
+    }
+
+    /// Records `amount` as spent at `now`, resetting the epoch first if it has ended.
+    /// Returns false, leaving the record untouched, if the amount exceeds what is left.
+    pub fn try_spend(&mut self, now: i64, amount: u64) -> bool {
+        if amount > self.effective_remaining(now) {
+            return false;
+        }
+        if self.has_rolled_over(now) {
+            self.roll_over(now);
+        }
+        self.spent_amount += amount;
+        true
+    }
+}
+
+/// Epoch spending limit assigned to a payee
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Default)]
+pub struct PayeeEpochLimit {
+    pub payee: Pubkey,               // Payee the limit applies to
+    pub mint: Option<Pubkey>,        // Mint the limit applies to (None = SOL)
+    pub spending: EpochSpending,     // Limit and spending in the current epoch
+}
+
+/// Human-readable label attached to a payee
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Default)]
+pub struct PayeeLabel {
+    pub payee: Pubkey,               // Labelled payee
+    pub label: [u8; 32],             // UTF-8 label padded with zeroes
+}
+
+/// Account allowed to trigger a payee's claims on their behalf
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Default)]
+pub struct PayeeDelegate {
+    pub payee: Pubkey,               // Payee the claims are paid to
+    pub delegate: Pubkey,            // Signer allowed to trigger them
+}
+
+/// Last time a payee withdrew or claimed, used to spot idle payees
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Default)]
+pub struct PayeeActivity {
+    pub payee: Pubkey,               // Tracked payee
+    pub last_activity: i64,          // Last withdrawal or claim (time added if none yet)
+}
+
+/// A large withdrawal waiting out the vault's withdrawal delay
+#[derive(AnchorDeserialize, AnchorSerialize, Clone)]
+pub struct PendingWithdrawal {
+    pub requester: Pubkey,           // Admin or payee who requested the withdrawal
+    pub amount: u64,                 // Amount to withdraw
+    pub execute_after: i64,          // Earliest time the withdrawal can be executed
+    pub memo: [u8; 64],              // Reason code recorded when executed (all zeroes if none)
+}
+
+/// Role a signer holds over a vault, as resolved by `VaultState::authorize`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Authority {
+    Admin,                           // May withdraw without payee limits
+    Payee,                           // May withdraw within limits and claim schedules
+    Unauthorized,                    // Holds no role over the vault
 }
 
 /// Main vault state account
 #[account]
+#[derive(Default)]
 pub struct VaultState {
     pub vault_bump: u8,
     pub state_bump: u8,
     pub admin: Pubkey,
+    pub creator: Pubkey,             // Admin the state PDA was derived from (never changes)
     pub payees: Vec<Pubkey>,         // List of authorized payees
     pub payout_schedules: Vec<PayoutSchedule>,  // Scheduled payouts for each payee
-    pub epoch_limits: Vec<(Pubkey, EpochSpending)>,  // Spending limits per payee
-    pub is_initialized: bool,
+    pub epoch_limits: Vec<PayeeEpochLimit>,  // Spending limits per payee and mint
+    pub restricted_deposits: bool,   // Whether only whitelisted depositors may deposit
+    pub depositors: Vec<Pubkey>,     // Depositors allowed when deposits are restricted
+    pub max_payees: u16,             // Maximum number of payees
+    pub max_schedules: u16,          // Maximum number of payout schedules
+    pub pending_admin: Option<Pubkey>,  // Proposed admin awaiting acceptance
+    pub max_balance: u64,            // Cap on usable vault balance for deposits (0 = unlimited)
+    pub instant_limit: u64,          // Largest withdrawal that bypasses the delay
+    pub withdrawal_delay: i64,       // Delay in seconds for larger withdrawals (0 = disabled)
+    pub pending_withdrawal: Option<PendingWithdrawal>,  // Withdrawal awaiting its delay
+    pub version: u8,                 // Layout version, see CURRENT_VERSION
+    pub is_paused: bool,             // Whether withdrawals and claims are halted
+    pub wsol_enabled: bool,          // Whether the vault holds a wrapped SOL token account
+    pub min_interval: i64,           // Shortest payout interval allowed, in seconds
+    pub payee_labels: Vec<PayeeLabel>,  // Human-readable payee labels (all-zero = unlabelled)
+    pub name: [u8; 32],              // Human-readable vault name, UTF-8 padded with zeroes
+    pub payee_activity: Vec<PayeeActivity>,  // Last withdrawal or claim of each payee
+    pub track_admin_spending: bool,  // Whether admin withdrawals are counted in the totals
+    pub admin_withdrawn: u64,        // Admin withdrawals made while tracking was on
+    pub total_withdrawn: u64,        // All tracked withdrawals (payees always, admin if tracked)
+    pub global_epoch: EpochSpending, // Cap on tracked withdrawals across all users (limit 0 = off)
+    pub require_withdraw_memo: bool, // Whether withdrawals must go through withdraw_with_memo
+    pub vault_nonce: u64,            // Distinguishes successive vaults of the same creator
+    pub payee_delegates: Vec<PayeeDelegate>, // Claim delegates of payees that registered one
+    pub velocity_window: i64,        // Length of the outflow window watched by the circuit breaker
+    pub velocity_limit: u64,         // Outflow within a window that pauses the vault (0 = off)
+    pub velocity_updated_at: i64,    // Last time velocity_outflow was decayed and added to
+    pub velocity_outflow: u64,       // Recent outflow, decaying to zero over one window
+}
+
+impl VaultState {
+    /// Nonce component of the state PDA seeds. Nonce 0 adds no seed bytes, so a creator's
+    /// first vault (and any vault from before nonces) lives at `[b"state", creator]`.
+    pub fn nonce_seed(vault_nonce: u64) -> Vec<u8> {
+        if vault_nonce == 0 {
+            Vec::new()
+        } else {
+            vault_nonce.to_le_bytes().to_vec()
+        }
+    }
+
+    /// Checks that `state_key` is the canonical state PDA of this vault and that the stored
+    /// bumps are the canonical bumps of the state and vault PDAs, as signer seeds rely on them
+    pub fn check_bumps(&self, state_key: &Pubkey) -> Result<()> {
+        let nonce_seed = Self::nonce_seed(self.vault_nonce);
+        let (state_address, state_bump) = Pubkey::find_program_address(
+            &[b"state", self.creator.as_ref(), nonce_seed.as_ref()],
+            &crate::ID,
+        );
+        require!(
+            state_address == *state_key && state_bump == self.state_bump,
+            CustomError::BumpMismatch
+        );
+        let (_, vault_bump) = Pubkey::find_program_address(&[b"vault", state_key.as_ref()], &crate::ID);
+        require!(vault_bump == self.vault_bump, CustomError::BumpMismatch);
+        Ok(())
+    }
+
+    /// Active schedules belonging to `payee`, with their indices. Split schedules all
+    /// store the default pubkey as their payee, so they are never matched here and are
+    /// addressed by index instead.
+    fn payee_schedules<'a>(&'a self, payee: &'a Pubkey) -> impl Iterator<Item = (usize, &'a PayoutSchedule)> + 'a {
+        self.payout_schedules
+            .iter()
+            .enumerate()
+            .filter(move |(_, s)| s.is_active && s.splits.is_empty() && s.payee == *payee)
+    }
+
+    /// Index of the active payout schedule belonging to `payee` at `now`, if any. A stream
+    /// that has finished vesting only comes up when the payee holds no other schedule.
+    pub fn active_schedule_index(&self, payee: &Pubkey, now: i64) -> Option<usize> {
+        self.payee_schedules(payee)
+            .find(|(_, s)| !s.has_finished_vesting(now))
+            .or_else(|| self.payee_schedules(payee).next())
+            .map(|(i, _)| i)
+    }
+
+    /// Index of the active stream `payee` claims from at `now`, if any. A stream that has
+    /// finished vesting is drained before one the payee was given after it.
+    pub fn active_stream_index(&self, payee: &Pubkey, now: i64) -> Option<usize> {
+        self.payee_schedules(payee)
+            .filter(|(_, s)| s.kind.is_stream())
+            .min_by_key(|(_, s)| !s.has_finished_vesting(now))
+            .map(|(i, _)| i)
+    }
+
+    /// Whether `payee` holds an active schedule at `now` that still pays out in future.
+    /// A stream that has finished vesting does not count, so a cancelled stream waiting
+    /// for its payee to claim what vested does not block a new schedule.
+    pub fn holds_open_schedule(&self, payee: &Pubkey, now: i64) -> bool {
+        self.payee_schedules(payee).any(|(_, s)| !s.has_finished_vesting(now))
+    }
+
+    /// Index of the active split schedule at `schedule_index`, if it is one
+    pub fn active_split_index(&self, schedule_index: u16) -> Option<usize> {
+        let index = schedule_index as usize;
+        self.payout_schedules
+            .get(index)
+            .filter(|s| s.is_active && !s.splits.is_empty())
+            .map(|_| index)
+    }
+
+    /// Epoch limit of `payee` for `mint` (None = SOL), if one was set
+    pub fn epoch_limit(&self, payee: &Pubkey, mint: Option<Pubkey>) -> Option<&EpochSpending> {
+        self.epoch_limits
+            .iter()
+            .find(|e| e.payee == *payee && e.mint == mint)
+            .map(|e| &e.spending)
+    }
+
+    /// Mutable epoch limit of `payee` for `mint` (None = SOL), if one was set
+    pub fn epoch_limit_mut(&mut self, payee: &Pubkey, mint: Option<Pubkey>) -> Option<&mut EpochSpending> {
+        self.epoch_limits
+            .iter_mut()
+            .find(|e| e.payee == *payee && e.mint == mint)
+            .map(|e| &mut e.spending)
+    }
+
+    /// Checks a new schedule against the interval floor and `now`, and that its payee exists
+    pub fn validate_new_schedule(&self, schedule: &PayoutSchedule, now: i64) -> Result<()> {
+        // The interval floor stops payees from spamming claims
+        require!(
+            schedule.interval > 0 && schedule.interval >= self.min_interval,
+            CustomError::InvalidPayoutSchedule
+        );
+        require!(schedule.next_payout_time > now, CustomError::InvalidPayoutSchedule);
+        if schedule.splits.is_empty() {
+            require!(self.payees.contains(&schedule.payee), CustomError::PayeeNotFound);
+            // A payee can only hold one open schedule, which claims look up by payee
+            require!(
+                !self.holds_open_schedule(&schedule.payee, now),
+                CustomError::InvalidPayoutSchedule
+            );
+        }
+        require!(
+            self.payout_schedules.len() < self.max_schedules as usize,
+            CustomError::MaxSchedulesReached
+        );
+        Ok(())
+    }
+
+    /// Records `amount` of SOL paid to `payee` at `now` against their epoch limit, if any.
+    /// Withdrawals and scheduled claims share the limit, so schedules cannot bypass it.
+    pub fn charge_payee_limit(&mut self, payee: &Pubkey, amount: u64, now: i64) -> Result<()> {
+        self.charge_payee_mint_limit(payee, None, amount, now)
+    }
+
+    /// Records `amount` of `mint` (None = SOL) paid to `payee` at `now` against their
+    /// epoch limit for that mint, if any. Each mint's limit is charged only in its own units.
+    pub fn charge_payee_mint_limit(
+        &mut self,
+        payee: &Pubkey,
+        mint: Option<Pubkey>,
+        amount: u64,
+        now: i64,
+    ) -> Result<()> {
+        if let Some(epoch_spending) = self.epoch_limit_mut(payee, mint) {
+            // set_epoch_limit never stores a zero duration, but a corrupt or defaulted
+            // record must fail cleanly rather than reset the limit on every payment
+            require!(epoch_spending.duration > 0, CustomError::InvalidEpochConfig);
+            // Rejects payments beyond what is left of the current epoch
+            require!(epoch_spending.try_spend(now, amount), CustomError::EpochSpendingLimitReached);
+        }
+        Ok(())
+    }
+
+    /// Rejects a deposit of `amount` that would lift `usable_balance` above `max_balance`
+    /// (0 = no cap). A vault holding exactly its rent floor has a usable balance of 0, and
+    /// a sum that overflows u64 is over any cap.
+    pub fn check_deposit_cap(&self, usable_balance: u64, amount: u64) -> Result<()> {
+        if self.max_balance > 0 {
+            let after = usable_balance
+                .checked_add(amount)
+                .ok_or(error!(CustomError::DepositCapExceeded))?;
+            require!(after <= self.max_balance, CustomError::DepositCapExceeded);
+        }
+        Ok(())
+    }
+
+    /// Role `key` holds over the vault. The admin role wins when the admin is also
+    /// listed as a payee, so every path treats such a signer as the admin.
+    pub fn authorize(&self, key: &Pubkey) -> Authority {
+        if *key == self.admin {
+            Authority::Admin
+        } else if self.payees.contains(key) {
+            Authority::Payee
+        } else {
+            Authority::Unauthorized
+        }
+    }
+
+    /// Records a SOL withdrawal of `amount` by `user` at `now` against the payee's epoch
+    /// limit and the vault-wide cap. The admin is exempt from payee limits and only counts
+    /// towards the cap when admin spending is tracked. On error the caller must abort, as
+    /// a payee limit may already have been charged.
+    pub fn spend_allowance(&mut self, user: &Pubkey, amount: u64, now: i64) -> Result<()> {
+        let is_admin = self.authorize(user) == Authority::Admin;
+        if !is_admin {
+            self.charge_payee_limit(user, amount, now)?;
+        }
+        // The vault-wide cap covers every payee, and the admin when admin spending is tracked
+        if !is_admin || self.track_admin_spending {
+            self.charge_global_limit(amount, now)?;
+        }
+        Ok(())
+    }
+
+    /// Adds a withdrawal of `amount` to the running totals. Payee withdrawals always
+    /// count, admin withdrawals only while admin spending is tracked.
+    pub fn track_withdrawal(&mut self, is_admin: bool, amount: u64) {
+        if is_admin && self.track_admin_spending {
+            self.admin_withdrawn = self.admin_withdrawn.saturating_add(amount);
+        }
+        if !is_admin || self.track_admin_spending {
+            self.total_withdrawn = self.total_withdrawn.saturating_add(amount);
+        }
+    }
+
+    /// Records `amount` of SOL leaving the vault at `now` against the vault-wide cap, if
+    /// one is set. Withdrawals, scheduled claims and escheats all share the cap.
+    pub fn charge_global_limit(&mut self, amount: u64, now: i64) -> Result<()> {
+        if self.global_epoch.limit > 0 {
+            require!(self.global_epoch.duration > 0, CustomError::InvalidEpochConfig);
+            require!(self.global_epoch.try_spend(now, amount), CustomError::GlobalLimitReached);
+        }
+        Ok(())
+    }
+
+    /// Checks a delayed withdrawal request by `user` for `amount` at `now`. Only amounts
+    /// above the instant limit (so never zero) need the delay, and a payee may not lock
+    /// the single pending slot with more than is left of their epoch allowance.
+    pub fn check_withdrawal_request(&self, user: &Pubkey, amount: u64, now: i64) -> Result<()> {
+        require!(!self.is_paused, CustomError::VaultPaused);
+        let authority = self.authorize(user);
+        require!(authority != Authority::Unauthorized, CustomError::UnauthorizedPayee);
+        require!(amount > self.instant_limit, CustomError::WithinInstantLimit);
+        if authority == Authority::Payee {
+            if let Some(epoch_spending) = self.epoch_limit(user, None) {
+                require!(
+                    amount <= epoch_spending.effective_remaining(now),
+                    CustomError::EpochSpendingLimitReached
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Label attached to `payee`, or all zeroes if none was set
+    pub fn payee_label(&self, payee: &Pubkey) -> [u8; 32] {
+        self.payee_labels
+            .iter()
+            .find(|l| l.payee == *payee)
+            .map(|l| l.label)
+            .unwrap_or_default()
+    }
+
+    /// Last withdrawal or claim by `payee`, or 0 if none was recorded
+    pub fn last_activity(&self, payee: &Pubkey) -> i64 {
+        self.payee_activity
+            .iter()
+            .find(|a| a.payee == *payee)
+            .map_or(0, |a| a.last_activity)
+    }
+
+    /// Adds `amount` paid out at `now` to the circuit breaker's rolling outflow. Returns
+    /// true, pausing the vault, if the outflow now exceeds the velocity limit. The payment
+    /// that trips the breaker still goes through, as the pause would not persist otherwise.
+    pub fn record_outflow(&mut self, amount: u64, now: i64) -> bool {
+        if self.velocity_limit == 0 || self.velocity_window <= 0 {
+            return false;
+        }
+        self.velocity_outflow = self.decayed_outflow(now).saturating_add(amount);
+        self.velocity_updated_at = now;
+        if self.velocity_outflow > self.velocity_limit {
+            self.is_paused = true;
+            return true;
+        }
+        false
+    }
+
+    /// Rolling outflow at `now`. The recorded outflow decays in proportion to the part of
+    /// the window elapsed since it was last updated and is gone after a whole window, so
+    /// there is no window boundary across which a burst stops being counted.
+    pub fn decayed_outflow(&self, now: i64) -> u64 {
+        let elapsed = now.saturating_sub(self.velocity_updated_at).max(0);
+        if self.velocity_window <= 0 || elapsed >= self.velocity_window {
+            return 0;
+        }
+        (self.velocity_outflow as u128 * (self.velocity_window - elapsed) as u128
+            / self.velocity_window as u128) as u64
+    }
+
+    /// Delegate registered to claim for `payee`, if any
+    pub fn claim_delegate(&self, payee: &Pubkey) -> Option<Pubkey> {
+        self.payee_delegates
+            .iter()
+            .find(|d| d.payee == *payee)
+            .map(|d| d.delegate)
+    }
+
+    /// Records activity by `payee` at `now`
+    pub fn touch_payee(&mut self, payee: Pubkey, now: i64) {
+        match self.payee_activity.iter_mut().find(|a| a.payee == payee) {
+            Some(activity) => activity.last_activity = now,
+            None => self.payee_activity.push(PayeeActivity { payee, last_activity: now }),
+        }
+    }
+
+    /// Sum of the next due amount of every active schedule, given the vault's usable balance
+    pub fn pending_obligations(&self, usable_balance: u64) -> u64 {
+        self.payout_schedules
+            .iter()
+            .filter(|s| s.is_active)
+            .fold(0u64, |total, s| {
+                total
+                    .saturating_add(s.accrued)
+                    .saturating_add(s.kind.amount_for(usable_balance))
+            })
+    }
 }
 
 impl Space for VaultState {
-    // Calculate the required space for the VaultState account
-    // 8 discriminator + 1 vault_bump + 1 state_bump + 32 admin + 
-    // 4 vec length + (32 * 5) max payees + 
-    // 4 vec length + (8 + 8 + 8 + 1) * 5 max schedules + 
-    // 4 vec length + (32 + (8 + 8 + 8 + 8)) * 5 max epoch limits +
-    // 1 is_initialized
-    const INIT_SPACE: usize = 8 + 1 + 1 + 32 + 4 + (32 * 5) + 4 + (25 * 5) + 4 + (64 * 5) + 1;
+    // Calculate the required space for the VaultState account.
+    // Vectors are sized for the default caps; max_payees and max_schedules
+    // must not exceed what was allocated here.
+    // 8 discriminator + 1 vault_bump + 1 state_bump + 32 admin + 32 creator + 
+    // 4 vec length + 32 * max payees + 
+    // 4 vec length + (32 + (1 + 32) + 8 + 8 + 1 + 8 + 4 + (32 + 2) * max splits + 8 + (1 + 32) + 8 + 8 + (1 + 32) + 8) * max schedules + 
+    // 4 vec length + (32 + (1 + 32) + (8 + 8 + 8 + 8 + 1 + 8 + 8 + 1)) * max epoch limits +
+    // 1 restricted_deposits + 4 vec length + 32 * max depositors +
+    // 2 max_payees + 2 max_schedules + (1 + 32) pending_admin + 8 max_balance +
+    // 8 instant_limit + 8 withdrawal_delay + (1 + 32 + 8 + 8 + 64) pending_withdrawal +
+    // 1 version + 1 is_paused + 1 wsol_enabled + 8 min_interval +
+    // 4 vec length + (32 + 32) * max payees (labels) + 32 name +
+    // 4 vec length + (32 + 8) * max payees (activity) +
+    // 1 track_admin_spending + 8 admin_withdrawn + 8 total_withdrawn +
+    // (8 + 8 + 8 + 8 + 1 + 8 + 8 + 1) global_epoch + 1 require_withdraw_memo + 8 vault_nonce +
+    // 4 vec length + (32 + 32) * max payees (delegates) +
+    // 8 velocity_window + 8 velocity_limit + 8 velocity_updated_at + 8 velocity_outflow
+    const INIT_SPACE: usize = 8 + 1 + 1 + 32 + 32
+        + 4 + (32 * DEFAULT_MAX_PAYEES as usize)
+        + 4 + ((192 + 34 * MAX_SPLITS) * DEFAULT_MAX_SCHEDULES as usize)
+        + 4 + (115 * MAX_EPOCH_LIMITS)
+        + 1 + 4 + (32 * MAX_DEPOSITORS)
+        + 2 + 2 + (1 + 32) + 8
+        + 8 + 8 + (1 + 32 + 8 + 8 + 64)
+        + 1 + 1 + 1 + 8
+        + 4 + (64 * DEFAULT_MAX_PAYEES as usize) + 32
+        + 4 + (40 * DEFAULT_MAX_PAYEES as usize)
+        + 1 + 8 + 8
+        + 50 + 1 + 8
+        + 4 + (64 * DEFAULT_MAX_PAYEES as usize)
+        + 8 + 8 + 8 + 8;
+}
+
+/// A payee's SOL epoch spending at the time of a snapshot
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Default)]
+pub struct PayeeSpent {
+    pub payee: Pubkey,
+    pub spent: u64,                  // Spent in the epoch current at the snapshot (0 without a limit)
+}
+
+/// Point-in-time record of a vault's balance and payee spending, for reporting
+#[account]
+#[derive(Default)]
+pub struct VaultSnapshot {
+    pub vault_state: Pubkey,         // Vault state the snapshot was taken of
+    pub slot: u64,                   // Slot the snapshot is filed under (part of its seeds)
+    pub timestamp: i64,              // Time the snapshot was taken
+    pub balance: u64,                // Usable vault balance above the rent floor
+    pub payee_count: u16,            // Number of authorized payees
+    pub payee_spent: Vec<PayeeSpent>, // Epoch spending of each payee
+    pub bump: u8,                    // Bump of the snapshot PDA
+}
+
+impl Space for VaultSnapshot {
+    // 8 discriminator + 32 vault_state + 8 slot + 8 timestamp + 8 balance +
+    // 2 payee_count + 4 vec length + (32 + 8) * max payees + 1 bump
+    const INIT_SPACE: usize = 8 + 32 + 8 + 8 + 8 + 2
+        + 4 + (40 * DEFAULT_MAX_PAYEES as usize)
+        + 1;
+}
+
+/// Per-admin record of the next vault nonce, so closed vault addresses are never reused
+#[account]
+#[derive(Default)]
+pub struct VaultCounter {
+    pub next_nonce: u64,             // Lowest nonce the admin's next vault may use
+    pub bump: u8,                    // Bump of the counter PDA
+}
+
+impl Space for VaultCounter {
+    // 8 discriminator + 8 next_nonce + 1 bump
+    const INIT_SPACE: usize = 8 + 8 + 1;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAYEE_LIMIT: u64 = 500;
+    const DAY: i64 = 86_400;
+
+    fn vault_with_payees(payees: &[Pubkey]) -> VaultState {
+        VaultState {
+            admin: Pubkey::new_unique(),
+            payees: payees.to_vec(),
+            max_schedules: DEFAULT_MAX_SCHEDULES,
+            min_interval: DEFAULT_MIN_INTERVAL,
+            epoch_limits: payees
+                .iter()
+                .map(|payee| PayeeEpochLimit {
+                    payee: *payee,
+                    mint: None,
+                    spending: EpochSpending {
+                        epoch_start: 0,
+                        limit: PAYEE_LIMIT,
+                        duration: DAY,
+                        ..Default::default()
+                    },
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn error_code(result: Result<()>) -> u32 {
+        match result {
+            Err(Error::AnchorError(e)) => e.error_code_number,
+            _ => panic!("expected an anchor error"),
+        }
+    }
+
+    #[test]
+    fn payee_allowance_resets_when_the_epoch_rolls_over() {
+        let payee = Pubkey::new_unique();
+        let mut state = vault_with_payees(&[payee]);
+
+        state.spend_allowance(&payee, 400, 10).unwrap();
+        assert_eq!(
+            error_code(state.spend_allowance(&payee, 200, DAY - 1)),
+            u32::from(CustomError::EpochSpendingLimitReached)
+        );
+        let spending = state.epoch_limit(&payee, None).unwrap();
+        assert_eq!(spending.effective_resets_at(DAY - 1), DAY);
+        // Past the boundary the reported window is the one a spend would start
+        assert_eq!(spending.effective_resets_at(DAY + 5), 2 * DAY + 5);
+        // A second past the epoch boundary the full limit is available again
+        state.spend_allowance(&payee, PAYEE_LIMIT, DAY).unwrap();
+        let spending = state.epoch_limit(&payee, None).unwrap();
+        assert_eq!(spending.epoch_start, DAY);
+        assert_eq!(spending.spent_amount, PAYEE_LIMIT);
+    }
+
+    #[test]
+    fn claims_and_withdrawals_share_the_payee_limit() {
+        let payee = Pubkey::new_unique();
+        let mut state = vault_with_payees(&[payee]);
+
+        state.charge_payee_limit(&payee, 300, 10).unwrap();
+        assert_eq!(
+            error_code(state.spend_allowance(&payee, 300, 20)),
+            u32::from(CustomError::EpochSpendingLimitReached)
+        );
+        state.spend_allowance(&payee, 200, 20).unwrap();
+        assert!(state.charge_payee_limit(&payee, 1, 30).is_err());
+        // A token limit is charged on its own, whatever is left of the SOL limit
+        let mint = Pubkey::new_unique();
+        state.epoch_limits.push(PayeeEpochLimit {
+            payee,
+            mint: Some(mint),
+            spending: EpochSpending { limit: 50, duration: DAY, ..Default::default() },
+        });
+        state.charge_payee_mint_limit(&payee, Some(mint), 50, 30).unwrap();
+        assert_eq!(
+            error_code(state.charge_payee_mint_limit(&payee, Some(mint), 1, 30)),
+            u32::from(CustomError::EpochSpendingLimitReached)
+        );
+        // Payees without a limit are not restricted
+        let other = Pubkey::new_unique();
+        state.charge_payee_limit(&other, u64::MAX, 30).unwrap();
+    }
+
+    #[test]
+    fn global_cap_adds_up_payees_and_skips_untracked_admin() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut state = vault_with_payees(&[a, b]);
+        state.global_epoch = EpochSpending { limit: 600, duration: DAY, ..Default::default() };
+
+        state.spend_allowance(&a, 400, 0).unwrap();
+        assert_eq!(
+            error_code(state.spend_allowance(&b, 300, 0)),
+            u32::from(CustomError::GlobalLimitReached)
+        );
+        state.spend_allowance(&b, 200, 0).unwrap();
+        let admin = state.admin;
+        state.spend_allowance(&admin, 1_000, 0).unwrap();
+
+        state.track_admin_spending = true;
+        assert_eq!(
+            error_code(state.spend_allowance(&admin, 1, 0)),
+            u32::from(CustomError::GlobalLimitReached)
+        );
+        // The global epoch resets on its own schedule
+        state.spend_allowance(&admin, 600, DAY).unwrap();
+    }
+
+    #[test]
+    fn totals_count_admin_withdrawals_only_when_tracked() {
+        let mut state = VaultState::default();
+
+        state.track_withdrawal(false, 100);
+        state.track_withdrawal(true, 50);
+        assert_eq!((state.admin_withdrawn, state.total_withdrawn), (0, 100));
+        state.track_admin_spending = true;
+        state.track_withdrawal(true, 50);
+        assert_eq!((state.admin_withdrawn, state.total_withdrawn), (50, 150));
+    }
+
+    #[test]
+    fn withdrawal_requests_need_a_delayable_affordable_amount() {
+        let payee = Pubkey::new_unique();
+        let mut state = vault_with_payees(&[payee]);
+        state.instant_limit = 100;
+        let admin = state.admin;
+
+        for amount in [0, 100] {
+            assert_eq!(
+                error_code(state.check_withdrawal_request(&payee, amount, 0)),
+                u32::from(CustomError::WithinInstantLimit)
+            );
+        }
+        // A payee's request must fit what is left of their epoch allowance
+        state.spend_allowance(&payee, 300, 0).unwrap();
+        assert_eq!(
+            error_code(state.check_withdrawal_request(&payee, PAYEE_LIMIT, 0)),
+            u32::from(CustomError::EpochSpendingLimitReached)
+        );
+        state.check_withdrawal_request(&payee, 200, 0).unwrap();
+        state.check_withdrawal_request(&payee, PAYEE_LIMIT, DAY).unwrap();
+        state.check_withdrawal_request(&admin, 10 * PAYEE_LIMIT, 0).unwrap();
+
+        state.is_paused = true;
+        assert_eq!(
+            error_code(state.check_withdrawal_request(&admin, 200, 0)),
+            u32::from(CustomError::VaultPaused)
+        );
+    }
+
+    #[test]
+    fn scheduled_payouts_share_the_global_cap() {
+        let payee = Pubkey::new_unique();
+        let mut state = vault_with_payees(&[payee]);
+        state.global_epoch = EpochSpending { limit: 600, duration: DAY, ..Default::default() };
+
+        // A claim and a withdrawal draw from the same allowance
+        state.charge_global_limit(500, 0).unwrap();
+        assert_eq!(
+            error_code(state.spend_allowance(&payee, 200, 0)),
+            u32::from(CustomError::GlobalLimitReached)
+        );
+        assert_eq!(
+            error_code(state.charge_global_limit(101, 0)),
+            u32::from(CustomError::GlobalLimitReached)
+        );
+        state.charge_global_limit(100, 0).unwrap();
+    }
+
+    #[test]
+    fn catch_up_claim_pays_only_affordable_periods() {
+        let mut schedule = PayoutSchedule {
+            kind: PayoutKind::Fixed(100),
+            next_payout_time: 1_000,
+            interval: 60,
+            is_active: true,
+            ..Default::default()
+        };
+        assert!(!schedule.is_due(999));
+
+        // Three periods are due at 1_120, but the balance only covers two
+        let now = 1_120;
+        assert_eq!(schedule.claim_amount(now, 250).unwrap(), (200, 2));
+        schedule.mark_claimed(2, 0);
+        assert_eq!(schedule.next_payout_time, 1_120);
+
+        // The third period is still owed, on top of anything accrued
+        schedule.accrued = 30;
+        assert_eq!(schedule.claim_amount(now, 130).unwrap(), (130, 1));
+        assert!(schedule.claim_amount(now, 20).is_err());
+        schedule.mark_claimed(1, 0);
+        assert_eq!(schedule.accrued, 0);
+        assert!(!schedule.is_due(now));
+    }
+
+    #[test]
+    fn next_claim_time_is_now_once_due() {
+        let mut schedule = PayoutSchedule {
+            kind: PayoutKind::Fixed(100),
+            next_payout_time: 1_000,
+            interval: 60,
+            is_active: true,
+            ..Default::default()
+        };
+        assert_eq!(schedule.next_claim_time(900), 1_000);
+        assert_eq!(schedule.next_claim_time(1_500), 1_500);
+        // Accrued amounts are claimable right away, but not before the cliff
+        schedule.accrued = 10;
+        assert_eq!(schedule.next_claim_time(900), 900);
+        schedule.cliff_time = 2_000;
+        assert_eq!(schedule.next_claim_time(900), 2_000);
+    }
+
+    #[test]
+    fn capped_claim_defers_the_rest() {
+        let mut schedule = PayoutSchedule {
+            kind: PayoutKind::Fixed(100),
+            next_payout_time: 1_000,
+            interval: 60,
+            is_active: true,
+            max_per_claim: 150,
+            ..Default::default()
+        };
+        // Three periods are due, but one claim pays at most 150
+        let (due, periods) = schedule.claim_amount(1_120, u64::MAX).unwrap();
+        assert_eq!((due, periods), (300, 3));
+        let paid = schedule.capped(due);
+        assert_eq!(paid, 150);
+        schedule.mark_claimed(periods, due - paid);
+        assert_eq!(schedule.next_payout_time, 1_180);
+
+        // The deferred amount is claimable right away, again capped
+        assert!(schedule.is_due(1_120));
+        let (due, periods) = schedule.claim_amount(1_120, u64::MAX).unwrap();
+        assert_eq!((due, periods), (150, 0));
+        assert_eq!(schedule.capped(due), 150);
+    }
+
+    #[test]
+    fn stream_vests_linearly_and_clamps_at_the_end() {
+        let mut kind = PayoutKind::Stream {
+            total_amount: 1_000,
+            start_time: 100,
+            end_time: 200,
+            claimed_so_far: 0,
+        };
+        // 0%: nothing before or at the start
+        assert_eq!(kind.stream_claimable(50), 0);
+        assert_eq!(kind.stream_claimable(100), 0);
+        // 50%: half the total, less what was already claimed
+        assert_eq!(kind.stream_claimable(150), 500);
+        if let PayoutKind::Stream { claimed_so_far, .. } = &mut kind {
+            *claimed_so_far = 500;
+        }
+        assert_eq!(kind.stream_claimable(150), 0);
+        // Past 100%: clamped to the total
+        assert_eq!(kind.vested_at(10_000), 1_000);
+        assert_eq!(kind.stream_claimable(10_000), 500);
+        // Streams are not paid per period
+        let schedule = PayoutSchedule { kind, next_payout_time: 100, interval: 100, ..Default::default() };
+        assert!(schedule.claim_amount(10_000, u64::MAX).is_err());
+    }
+
+    #[test]
+    fn new_schedule_is_checked_against_the_given_time() {
+        let payee = Pubkey::new_unique();
+        let mut state = vault_with_payees(&[payee]);
+        let schedule = PayoutSchedule {
+            payee,
+            kind: PayoutKind::Fixed(100),
+            next_payout_time: 5_000,
+            interval: DEFAULT_MIN_INTERVAL,
+            is_active: true,
+            ..Default::default()
+        };
+
+        state.validate_new_schedule(&schedule, 4_999).unwrap();
+        assert!(state.validate_new_schedule(&schedule, 5_000).is_err());
+        let too_frequent = PayoutSchedule { interval: DEFAULT_MIN_INTERVAL - 1, ..schedule.clone() };
+        assert!(state.validate_new_schedule(&too_frequent, 0).is_err());
+
+        // A second active schedule for the same payee is rejected until the first ends
+        state.payout_schedules.push(schedule.clone());
+        assert_eq!(
+            error_code(state.validate_new_schedule(&schedule, 0)),
+            u32::from(CustomError::InvalidPayoutSchedule)
+        );
+        state.payout_schedules[0].is_active = false;
+        state.validate_new_schedule(&schedule, 0).unwrap();
+    }
+
+    #[test]
+    fn payee_lookups_skip_split_schedules() {
+        let payee = Pubkey::new_unique();
+        let mut state = vault_with_payees(&[payee]);
+        state.payout_schedules.push(PayoutSchedule {
+            payee: Pubkey::default(),
+            kind: PayoutKind::Fixed(100),
+            is_active: true,
+            splits: vec![PayoutSplit { recipient: payee, weight_bps: 10_000 }],
+            ..Default::default()
+        });
+        state.payout_schedules.push(PayoutSchedule {
+            payee,
+            kind: PayoutKind::Fixed(100),
+            is_active: true,
+            ..Default::default()
+        });
+
+        // The split's default payee never matches a payee lookup
+        assert_eq!(state.active_schedule_index(&Pubkey::default(), 0), None);
+        assert_eq!(state.active_schedule_index(&payee, 0), Some(1));
+        // Splits are found by index, and only while active
+        assert_eq!(state.active_split_index(0), Some(0));
+        assert_eq!(state.active_split_index(1), None);
+        assert_eq!(state.active_split_index(2), None);
+        state.payout_schedules[0].is_active = false;
+        assert_eq!(state.active_split_index(0), None);
+    }
+
+    #[test]
+    fn authorize_resolves_each_role() {
+        let payee = Pubkey::new_unique();
+        let mut state = vault_with_payees(&[payee]);
+        let admin = state.admin;
+
+        assert_eq!(state.authorize(&admin), Authority::Admin);
+        assert_eq!(state.authorize(&payee), Authority::Payee);
+        assert_eq!(state.authorize(&Pubkey::new_unique()), Authority::Unauthorized);
+        // An admin listed as a payee is still treated as the admin
+        state.payees.push(admin);
+        assert_eq!(state.authorize(&admin), Authority::Admin);
+        // Removed payees lose their role
+        state.payees.retain(|p| *p != payee);
+        assert_eq!(state.authorize(&payee), Authority::Unauthorized);
+    }
+
+    #[test]
+    fn deposit_cap_holds_at_the_rent_floor_and_on_overflow() {
+        let state = VaultState { max_balance: 1_000, ..Default::default() };
+
+        // A vault at exactly its rent floor can be filled up to the cap
+        state.check_deposit_cap(0, 1_000).unwrap();
+        assert_eq!(
+            error_code(state.check_deposit_cap(0, 1_001)),
+            u32::from(CustomError::DepositCapExceeded)
+        );
+        // Just above the cap, even the smallest deposit is rejected
+        assert!(state.check_deposit_cap(1_000, 1).is_err());
+        assert_eq!(
+            error_code(state.check_deposit_cap(1, u64::MAX)),
+            u32::from(CustomError::DepositCapExceeded)
+        );
+        // No cap: anything that fits in u64 goes
+        let uncapped = VaultState::default();
+        uncapped.check_deposit_cap(1, u64::MAX - 1).unwrap();
+    }
+
+    #[test]
+    fn cancelled_stream_keeps_what_vested() {
+        let mut schedule = PayoutSchedule {
+            kind: PayoutKind::Stream { total_amount: 1_000, start_time: 100, end_time: 200, claimed_so_far: 100 },
+            is_active: true,
+            ..Default::default()
+        };
+
+        // Cancelled at 40% vested with 100 already claimed
+        assert_eq!(schedule.cancel_stream(140), (300, 600));
+        assert!(schedule.is_active);
+        assert_eq!(schedule.kind.stream_claimable(140), 300);
+        // Nothing more vests after the cancellation
+        assert_eq!(schedule.kind.stream_claimable(10_000), 300);
+
+        // A stream cancelled before it starts owes nothing and is closed right away
+        let mut unstarted = PayoutSchedule {
+            kind: PayoutKind::Stream { total_amount: 1_000, start_time: 100, end_time: 200, claimed_so_far: 0 },
+            is_active: true,
+            ..Default::default()
+        };
+        assert_eq!(unstarted.cancel_stream(50), (0, 1_000));
+        assert!(!unstarted.is_active);
+    }
+
+    #[test]
+    fn cancelled_stream_does_not_block_a_new_schedule() {
+        let payee = Pubkey::new_unique();
+        let mut state = vault_with_payees(&[payee]);
+        state.payout_schedules.push(PayoutSchedule {
+            payee,
+            kind: PayoutKind::Stream { total_amount: 1_000, start_time: 100, end_time: 200, claimed_so_far: 0 },
+            next_payout_time: 100,
+            interval: 100,
+            is_active: true,
+            ..Default::default()
+        });
+        let fixed = PayoutSchedule {
+            payee,
+            kind: PayoutKind::Fixed(100),
+            next_payout_time: 5_000,
+            interval: DEFAULT_MIN_INTERVAL,
+            is_active: true,
+            ..Default::default()
+        };
+        assert!(state.validate_new_schedule(&fixed, 140).is_err());
+
+        // Cancelled with a vested balance left to claim, the stream stays active
+        state.payout_schedules[0].cancel_stream(140);
+        assert!(state.payout_schedules[0].is_active);
+        state.validate_new_schedule(&fixed, 140).unwrap();
+        state.payout_schedules.push(fixed);
+
+        // Claims find the new schedule, and stream claims still drain the old stream
+        assert_eq!(state.active_schedule_index(&payee, 140), Some(1));
+        assert_eq!(state.active_stream_index(&payee, 140), Some(0));
+        assert!(state.holds_open_schedule(&payee, 140));
+    }
+
+    #[test]
+    fn zero_duration_epoch_fails_cleanly() {
+        let payee = Pubkey::new_unique();
+        let mut state = vault_with_payees(&[payee]);
+        state.epoch_limits[0].spending.duration = 0;
+
+        assert_eq!(
+            error_code(state.spend_allowance(&payee, 1, DAY)),
+            u32::from(CustomError::InvalidEpochConfig)
+        );
+        assert_eq!(state.epoch_limits[0].spending.spent_amount, 0);
+
+        // The vault-wide cap is checked the same way
+        let other = Pubkey::new_unique();
+        state.payees.push(other);
+        state.global_epoch = EpochSpending { limit: 1_000, duration: 0, ..Default::default() };
+        assert_eq!(
+            error_code(state.spend_allowance(&other, 1, DAY)),
+            u32::from(CustomError::InvalidEpochConfig)
+        );
+    }
+
+    #[test]
+    fn self_reset_needs_permission_and_an_elapsed_epoch() {
+        let mut spending = EpochSpending {
+            epoch_start: 0,
+            spent_amount: PAYEE_LIMIT,
+            limit: PAYEE_LIMIT,
+            duration: DAY,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            error_code(spending.self_reset(DAY)),
+            u32::from(CustomError::UnauthorizedPayee)
+        );
+        spending.self_reset_allowed = true;
+        assert_eq!(
+            error_code(spending.self_reset(DAY - 1)),
+            u32::from(CustomError::EpochNotElapsed)
+        );
+        assert_eq!(spending.spent_amount, PAYEE_LIMIT);
+
+        // Once the epoch is over, the new one starts at the reset time
+        spending.self_reset(DAY + 100).unwrap();
+        assert_eq!(spending.epoch_start, DAY + 100);
+        assert_eq!(spending.spent_amount, 0);
+    }
+
+    #[test]
+    fn bumps_are_checked_against_the_canonical_ones() {
+        let creator = Pubkey::new_unique();
+        let vault_nonce: u64 = 3;
+        let (state_key, state_bump) = Pubkey::find_program_address(
+            &[b"state", creator.as_ref(), &vault_nonce.to_le_bytes()],
+            &crate::ID,
+        );
+        let (_, vault_bump) = Pubkey::find_program_address(&[b"vault", state_key.as_ref()], &crate::ID);
+        let mut state = VaultState { creator, vault_nonce, state_bump, vault_bump, ..Default::default() };
+
+        state.check_bumps(&state_key).unwrap();
+        assert!(state.check_bumps(&Pubkey::new_unique()).is_err());
+        state.vault_bump = vault_bump.wrapping_sub(1);
+        assert_eq!(
+            error_code(state.check_bumps(&state_key)),
+            u32::from(CustomError::BumpMismatch)
+        );
+    }
+
+    #[test]
+    fn circuit_breaker_counts_a_rolling_window() {
+        let mut state = VaultState { velocity_limit: 1_000, velocity_window: 60, ..Default::default() };
+
+        assert!(!state.record_outflow(600, 0));
+        // A whole window later the earlier outflow no longer counts
+        assert!(!state.record_outflow(600, 60));
+        // Half a window later, half of it still does
+        assert!(!state.record_outflow(300, 90));
+        assert_eq!(state.velocity_outflow, 600);
+        assert!(!state.is_paused);
+        // There is no boundary to straddle: a burst right after still adds up and trips
+        assert!(state.record_outflow(500, 92));
+        assert!(state.is_paused);
+        assert_eq!(state.velocity_outflow, 1_080);
+
+        // Windows and epochs too long to add to the current time do not overflow
+        let mut wide = VaultState { velocity_limit: 1_000, velocity_window: i64::MAX, ..Default::default() };
+        assert!(!wide.record_outflow(600, i64::MAX));
+        assert!(wide.record_outflow(600, i64::MAX));
+        let epoch = EpochSpending { epoch_start: 1, limit: 1_000, duration: i64::MAX, ..Default::default() };
+        assert!(!epoch.has_rolled_over(i64::MAX - 1));
+
+        // A disabled breaker never trips
+        let mut off = VaultState::default();
+        assert!(!off.record_outflow(u64::MAX, 0));
+        assert!(!off.is_paused);
+    }
 }
\ No newline at end of file
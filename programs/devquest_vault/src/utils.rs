@@ -0,0 +1,27 @@
+// src/utils.rs
+// Shared helpers for the instruction handlers
+
+use anchor_lang::prelude::*;
+
+/// Vault lamports available above the rent-exempt minimum
+pub fn usable_balance(vault: &SystemAccount) -> Result<u64> {
+    let rent_exempt = Rent::get()?.minimum_balance(vault.to_account_info().data_len());
+    Ok(vault.lamports().saturating_sub(rent_exempt))
+}
+
+/// Current cluster time. Handlers read the clock once through this and pass the
+/// timestamp down, so the time-dependent logic in `state` can be tested with any `now`.
+pub fn current_timestamp() -> Result<i64> {
+    Ok(Clock::get()?.unix_timestamp)
+}
+
+/// Current cluster slot
+pub fn current_slot() -> Result<u64> {
+    Ok(Clock::get()?.slot)
+}
+
+/// Whether a zero-padded fixed-size name holds valid UTF-8 before its padding
+pub fn is_valid_name(name: &[u8; 32]) -> bool {
+    let len = name.iter().rposition(|b| *b != 0).map_or(0, |i| i + 1);
+    std::str::from_utf8(&name[..len]).is_ok()
+}
\ No newline at end of file
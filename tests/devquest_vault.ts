@@ -5,6 +5,131 @@ import { DevquestVault } from "../target/types/devquest_vault";
 import { Keypair } from "@solana/web3.js";
 import { assert } from "chai";
 
+// Derive the vault state and vault PDAs owned by a given admin. Nonce 0 adds
+// no seed, so an admin's first vault lives at ["state", admin].
+const deriveVaultAccounts = (
+  programId: anchor.web3.PublicKey,
+  admin: anchor.web3.PublicKey,
+  nonce = 0
+) => {
+  const seeds = [Buffer.from("state"), admin.toBuffer()];
+  if (nonce > 0) {
+    seeds.push(new anchor.BN(nonce).toArrayLike(Buffer, "le", 8));
+  }
+  const vaultState = anchor.web3.PublicKey.findProgramAddressSync(
+    seeds,
+    programId
+  )[0];
+  const vault = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("vault"), vaultState.toBytes()],
+    programId
+  )[0];
+  return { vaultState, vault };
+};
+
+// Airdrop SOL to an account and wait for confirmation
+const airdrop = async (
+  provider: anchor.AnchorProvider,
+  to: anchor.web3.PublicKey,
+  sol: number
+) => {
+  const sig = await provider.connection.requestAirdrop(
+    to,
+    sol * anchor.web3.LAMPORTS_PER_SOL
+  );
+  await provider.connection.confirmTransaction(sig);
+};
+
+// Create an admin's first vault, deposit `deposit` lamports and register `payees`.
+// The admin gets enough SOL for the deposit plus 5 SOL for rent, fees and later
+// deposits, and each payee gets 1 SOL to sign with.
+const createFundedVault = async ({
+  admin,
+  payees = [],
+  deposit = 0,
+}: {
+  admin: Keypair;
+  payees?: Keypair[];
+  deposit?: number;
+}) => {
+  const provider = anchor.getProvider() as anchor.AnchorProvider;
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+  const { vaultState, vault } = deriveVaultAccounts(
+    program.programId,
+    admin.publicKey
+  );
+
+  await airdrop(
+    provider,
+    admin.publicKey,
+    Math.ceil(deposit / anchor.web3.LAMPORTS_PER_SOL) + 5
+  );
+  await program.methods
+    .initialize(NO_NAME, new anchor.BN(0))
+    .accountsPartial({ user: admin.publicKey, vaultState, vault })
+    .signers([admin])
+    .rpc();
+  if (deposit > 0) {
+    await program.methods
+      .deposit(new anchor.BN(deposit))
+      .accountsPartial({ user: admin.publicKey, vaultState, vault })
+      .signers([admin])
+      .rpc();
+  }
+  for (const payee of payees) {
+    await airdrop(provider, payee.publicKey, 1);
+    await program.methods
+      .addPayee(payee.publicKey, NO_NAME)
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+  }
+  return { vaultState, vault };
+};
+
+// Decode the Anchor events emitted in a transaction's logs
+const decodeEvents = (program: Program<DevquestVault>, logs: string[]) =>
+  logs
+    .filter((log) => log.startsWith("Program data: "))
+    .map((log) =>
+      program.coder.events.decode(log.slice("Program data: ".length))
+    )
+    .filter((event) => event !== null);
+
+// Fetch the events emitted by a confirmed transaction
+const fetchEvents = async (
+  program: Program<DevquestVault>,
+  signature: string
+) => {
+  const tx = await program.provider.connection.getTransaction(signature, {
+    commitment: "confirmed",
+    maxSupportedTransactionVersion: 0,
+  });
+  return decodeEvents(program, tx.meta.logMessages);
+};
+
+// Find an event by name, ignoring the casing of the IDL name
+const findEvent = (events: { name: string; data: any }[], name: string) =>
+  events.find((event) => event.name.toLowerCase() === name.toLowerCase());
+
+// Encode a payee label or vault name as the fixed 32-byte array the program stores
+const encodeName = (text: string) => {
+  const bytes = Buffer.alloc(32);
+  bytes.write(text, "utf8");
+  return Array.from(bytes);
+};
+
+// Decode a stored label or name, dropping the zero padding
+const decodeName = (bytes: number[]) =>
+  Buffer.from(bytes).toString("utf8").replace(/\0+$/, "");
+
+// All-zero value used when a payee or vault has no label or name
+const NO_NAME = encodeName("");
+
+// Wait for the given number of seconds (used to let schedules come due)
+const sleep = (seconds: number) =>
+  new Promise((resolve) => setTimeout(resolve, seconds * 1000));
+
 // Main test suite for the devquest-vault program
 describe("devquest-vault", () => {
   // Configure the client to use the local Solana cluster
@@ -46,20 +171,88 @@ describe("devquest-vault", () => {
   it("Is initialized!", async () => {
     // Call the initialize method on the program
     const tx = await program.methods
-      .initialize()
+      .initialize(encodeName("DevQuest Treasury"), new anchor.BN(0))
       .accountsPartial({
         user: provider.wallet.publicKey,
         vaultState,
         vault,
         systemProgram: anchor.web3.SystemProgram.programId,
       })
-      .rpc();
+      .rpc({ commitment: "confirmed" });
 
     console.log("\nYour transaction signature", tx);
     console.log(
       "Your vault info",
       await provider.connection.getAccountInfo(vault)
     );
+
+    const initialized = findEvent(
+      await fetchEvents(program, tx),
+      "VaultInitializedEvent"
+    );
+    assert.ok(initialized, "VaultInitializedEvent should be emitted");
+    assert.ok(initialized.data.vaultState.equals(vaultState));
+    assert.ok(initialized.data.admin.equals(admin));
+    assert.equal(decodeName(initialized.data.name), "DevQuest Treasury");
+  });
+
+  // Test: The vault name round-trips through get_vault_info
+  it("Returns vault info", async () => {
+    const info = await program.methods
+      .getVaultInfo()
+      .accountsPartial({ vaultState, vault })
+      .view();
+
+    assert.equal(decodeName(info.name), "DevQuest Treasury");
+    assert.ok(info.admin.equals(admin));
+    assert.ok(info.creator.equals(admin));
+    assert.isFalse(info.isPaused);
+    assert.equal(info.usableBalance.toNumber(), 0);
+    assert.equal(info.payeeCount, 0);
+    assert.equal(info.activeScheduleCount, 0);
+  });
+
+  // Test: Account usage reports the reserved space and what the state occupies
+  it("Returns account usage", async () => {
+    const usage = await program.methods
+      .getAccountUsage()
+      .accountsPartial({ vaultState, vault })
+      .view();
+    const info = await provider.connection.getAccountInfo(vaultState);
+
+    assert.equal(usage.allocated.toNumber(), info.data.length);
+    assert.equal(usage.initSpace.toNumber(), info.data.length);
+    assert.isAbove(usage.used.toNumber(), 0);
+    assert.isAtMost(usage.used.toNumber(), usage.allocated.toNumber());
+  });
+
+  // Test: The state PDA can only be initialized once per admin
+  it("Cannot initialize twice", async () => {
+    try {
+      await program.methods
+        .initialize(NO_NAME, new anchor.BN(0))
+        .accountsPartial({
+          user: provider.wallet.publicKey,
+          vaultState,
+          vault,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      assert.fail("Expected error when initializing an existing vault");
+    } catch (error) {
+      console.log("Successfully prevented re-initialization");
+      assert.isTrue(
+        error.logs.some((log: string) => log.includes("already in use"))
+      );
+    }
+  });
+
+  // Test: Payee and schedule caps are stored in state with their defaults
+  it("Stores default payee and schedule caps", async () => {
+    const state = await program.account.vaultState.fetch(vaultState);
+    assert.equal(state.maxPayees, 5);
+    assert.equal(state.maxSchedules, 5);
   });
 
   // Test: Deposit 2 SOL into the vault
@@ -106,20 +299,25 @@ describe("devquest-vault", () => {
 
   // Test: Admin can add payees to the vault
   it("Admin can add payees", async () => {
-    // Add first payee
+    // Add first payee with a label
     const tx1 = await program.methods
-      .addPayee(payee1.publicKey)
+      .addPayee(payee1.publicKey, encodeName("backend-contractor"))
       .accountsPartial({
         user: provider.wallet.publicKey,
         vaultState,
       })
-      .rpc();
+      .rpc({ commitment: "confirmed" });
 
     console.log("\nAdding first payee transaction signature", tx1);
 
-    // Add second payee
+    const added = findEvent(await fetchEvents(program, tx1), "PayeeAddedEvent");
+    assert.ok(added, "PayeeAddedEvent should be emitted");
+    assert.ok(added.data.payee.equals(payee1.publicKey));
+    assert.equal(decodeName(added.data.label), "backend-contractor");
+
+    // Add second payee without a label
     const tx2 = await program.methods
-      .addPayee(payee2.publicKey)
+      .addPayee(payee2.publicKey, NO_NAME)
       .accountsPartial({
         user: provider.wallet.publicKey,
         vaultState,
@@ -127,6 +325,12 @@ describe("devquest-vault", () => {
       .rpc();
 
     console.log("Adding second payee transaction signature", tx2);
+
+    // Only the labelled payee has a stored label
+    const state = await program.account.vaultState.fetch(vaultState);
+    assert.equal(state.payeeLabels.length, 1);
+    assert.ok(state.payeeLabels[0].payee.equals(payee1.publicKey));
+    assert.equal(decodeName(state.payeeLabels[0].label), "backend-contractor");
   });
 
   // Test: Only admin can add payees (unauthorized users are prevented)
@@ -134,7 +338,7 @@ describe("devquest-vault", () => {
     try {
       // Try to add a payee using unauthorized user (should fail)
       await program.methods
-        .addPayee(unauthorizedUser.publicKey)
+        .addPayee(unauthorizedUser.publicKey, NO_NAME)
         .accountsPartial({
           user: payee1.publicKey,
           vaultState,
@@ -178,6 +382,26 @@ describe("devquest-vault", () => {
     console.log("\nAuthorized payee withdrawal signature", tx);
   });
 
+  // Test: Withdrawing more than the vault holds fails with a custom error
+  it("Cannot withdraw more than the vault holds", async () => {
+    try {
+      await program.methods
+        .withdraw(new anchor.BN(1000 * anchor.web3.LAMPORTS_PER_SOL))
+        .accountsPartial({
+          user: provider.wallet.publicKey,
+          vaultState,
+          vault,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      assert.fail("Expected error when withdrawing more than the vault holds");
+    } catch (error) {
+      console.log("Successfully prevented overdrawing the vault");
+      assert.equal(error.error.errorCode.code, "InsufficientFunds");
+    }
+  });
+
   // Test: Unauthorized user cannot withdraw from the vault
   it("Unauthorized user cannot withdraw", async () => {
     try {
@@ -212,6 +436,20 @@ describe("devquest-vault", () => {
     console.log("\nRemoving payee transaction signature", tx);
   });
 
+  // Test: Admin lowers the payout interval floor so short test intervals are allowed
+  it("Admin can lower the minimum interval", async () => {
+    await program.methods
+      .setMinInterval(new anchor.BN(10))
+      .accountsPartial({
+        user: provider.wallet.publicKey,
+        vaultState,
+      })
+      .rpc();
+
+    const state = await program.account.vaultState.fetch(vaultState);
+    assert.equal(state.minInterval.toNumber(), 10);
+  });
+
   // Test: Admin can schedule a payout for a payee
   it("Admin can schedule payout", async () => {
     const now = Math.floor(Date.now() / 1000);
@@ -230,9 +468,61 @@ describe("devquest-vault", () => {
         user: provider.wallet.publicKey,
         vaultState,
       })
-      .rpc();
+      .rpc({ commitment: "confirmed" });
 
     console.log("\nScheduling payout transaction signature", tx);
+
+    const scheduled = findEvent(
+      await fetchEvents(program, tx),
+      "PayoutScheduledEvent"
+    );
+    assert.ok(scheduled.data.payee.equals(payee1.publicKey));
+    assert.equal(scheduled.data.interval.toNumber(), interval);
+    assert.equal(scheduled.data.nextPayoutTime.toNumber(), startTime);
+  });
+
+  // Test: Payee status reports the scheduled payout and empty epoch data
+  it("Reports payee status", async () => {
+    const status = await program.methods
+      .getPayeeStatus(payee1.publicKey)
+      .accountsPartial({ vault, vaultState })
+      .view();
+
+    assert.equal(
+      status.nextPayoutAmount.toString(),
+      (0.1 * anchor.web3.LAMPORTS_PER_SOL).toString()
+    );
+    assert.isNotNull(status.nextPayoutTime);
+    assert.equal(status.epochLimit.toNumber(), 0);
+
+    // A payee without limits or schedules gets zeros rather than an error
+    const removedStatus = await program.methods
+      .getPayeeStatus(payee2.publicKey)
+      .accountsPartial({ vault, vaultState })
+      .view();
+    assert.equal(removedStatus.epochLimit.toNumber(), 0);
+    assert.equal(removedStatus.epochSpent.toNumber(), 0);
+    assert.isNull(removedStatus.nextPayoutTime);
+    assert.isNull(removedStatus.nextPayoutAmount);
+  });
+
+  // Test: Admin can change the interval of a payout schedule
+  it("Admin can update payout schedule", async () => {
+    const amount = new anchor.BN(0.1 * anchor.web3.LAMPORTS_PER_SOL);
+    const tx = await program.methods
+      .updatePayout(payee1.publicKey, { fixed: { 0: amount } }, new anchor.BN(20))
+      .accountsPartial({
+        user: provider.wallet.publicKey,
+        vaultState,
+      })
+      .rpc({ commitment: "confirmed" });
+
+    const updated = findEvent(
+      await fetchEvents(program, tx),
+      "PayoutUpdatedEvent"
+    );
+    assert.ok(updated.data.payee.equals(payee1.publicKey));
+    assert.equal(updated.data.interval.toNumber(), 20);
   });
 
   // Test: Payee cannot claim payout before the scheduled time
@@ -253,6 +543,14 @@ describe("devquest-vault", () => {
     } catch (error) {
       console.log("Successfully prevented early payout claim");
       assert.equal(error.error.errorCode.code, "PayoutTimeNotReached");
+
+      // The near-miss is logged as a ClaimAttempt event for keepers
+      const attempt = findEvent(
+        decodeEvents(program, error.logs),
+        "ClaimAttempt"
+      );
+      assert.ok(attempt);
+      assert.ok(attempt.data.payee.equals(payee1.publicKey));
     }
   });
 
@@ -264,9 +562,15 @@ describe("devquest-vault", () => {
         user: provider.wallet.publicKey,
         vaultState,
       })
-      .rpc();
+      .rpc({ commitment: "confirmed" });
 
     console.log("\nCanceling payout schedule transaction signature", tx);
+
+    const cancelled = findEvent(
+      await fetchEvents(program, tx),
+      "PayoutCancelledEvent"
+    );
+    assert.ok(cancelled.data.payee.equals(payee1.publicKey));
   });
 
   // Test: Payee cannot claim from a cancelled payout schedule
@@ -286,7 +590,7 @@ describe("devquest-vault", () => {
       assert.fail("Should not be able to claim from cancelled schedule");
     } catch (error) {
       console.log("Successfully prevented claim from cancelled schedule");
-      assert.equal(error.error.errorCode.code, "ScheduleNotFound");
+      assert.equal(error.error.errorCode.code, "NoScheduleForPayee");
     }
   });
 
@@ -309,3 +613,3731 @@ describe("devquest-vault", () => {
     );
   });
 });
+
+// Test suite: the vault PDA can be funded by anyone before initialization
+describe("devquest-vault pre-funded vault", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  // A fresh admin gets its own vault, independent of the main suite
+  const admin = Keypair.generate();
+  const { vaultState, vault } = deriveVaultAccounts(
+    program.programId,
+    admin.publicKey
+  );
+
+  before(async () => {
+    await airdrop(provider, admin.publicKey, 2);
+  });
+
+  // Test: Lamports sent to the vault PDA before initialize are kept as vault funds
+  it("Initializes over a pre-funded vault PDA", async () => {
+    const preFund = 0.5 * anchor.web3.LAMPORTS_PER_SOL;
+    // Anyone (here the provider wallet) can send SOL to the derivable vault PDA
+    const preFundTx = new anchor.web3.Transaction().add(
+      anchor.web3.SystemProgram.transfer({
+        fromPubkey: provider.wallet.publicKey,
+        toPubkey: vault,
+        lamports: preFund,
+      })
+    );
+    await provider.sendAndConfirm(preFundTx);
+
+    await program.methods
+      .initialize(NO_NAME, new anchor.BN(0))
+      .accountsPartial({
+        user: admin.publicKey,
+        vaultState,
+        vault,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([admin])
+      .rpc();
+
+    // The pre-funded amount already covers rent, so no top-up is taken from the admin
+    const vaultBalance = await provider.connection.getBalance(vault);
+    assert.equal(vaultBalance, preFund);
+
+    // The state is owned by the admin who initialized, not by whoever funded the PDA
+    const state = await program.account.vaultState.fetch(vaultState);
+    assert.ok(state.admin.equals(admin.publicKey));
+    assert.equal(state.payees.length, 0);
+  });
+});
+
+// Test suite: schedules paying a percentage of the vault balance
+describe("devquest-vault percentage payouts", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const payee = Keypair.generate();
+  const { vaultState, vault } = deriveVaultAccounts(
+    program.programId,
+    admin.publicKey
+  );
+
+  before(async () => {
+    await createFundedVault({
+      admin,
+      payees: [payee],
+      deposit: 2 * anchor.web3.LAMPORTS_PER_SOL,
+    });
+  });
+
+  // Test: Percentages outside 1..=10000 bps are rejected
+  it("Rejects out-of-range percentages", async () => {
+    const startTime = new anchor.BN(Math.floor(Date.now() / 1000) + 60);
+    for (const bps of [0, 10001]) {
+      try {
+        await program.methods
+          .schedulePercentPayout(payee.publicKey, bps, startTime, new anchor.BN(10))
+          .accountsPartial({ user: admin.publicKey, vaultState })
+          .signers([admin])
+          .rpc();
+        assert.fail(`Expected ${bps} bps to be rejected`);
+      } catch (error) {
+        assert.equal(error.error.errorCode.code, "InvalidPayoutSchedule");
+      }
+    }
+  });
+
+  // Test: A 50% schedule pays half of the balance above the rent floor
+  it("Claims a percentage of the vault balance", async () => {
+    const startTime = Math.floor(Date.now() / 1000) + 2;
+    await program.methods
+      .schedulePercentPayout(
+        payee.publicKey,
+        5000,
+        new anchor.BN(startTime),
+        new anchor.BN(3600)
+      )
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+
+    await sleep(4);
+
+    const rentExempt =
+      await provider.connection.getMinimumBalanceForRentExemption(0);
+    const vaultBefore = await provider.connection.getBalance(vault);
+    await program.methods
+      .claimPayout()
+      .accountsPartial({ user: payee.publicKey, vaultState, vault })
+      .signers([payee])
+      .rpc();
+    const vaultAfter = await provider.connection.getBalance(vault);
+
+    const expected = Math.floor((vaultBefore - rentExempt) / 2);
+    assert.equal(vaultBefore - vaultAfter, expected);
+    assert.isAtLeast(vaultAfter, rentExempt);
+  });
+});
+
+// Test suite: claims larger than the vault balance
+describe("devquest-vault insufficient funds", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const payee = Keypair.generate();
+  const { vaultState, vault } = deriveVaultAccounts(
+    program.programId,
+    admin.publicKey
+  );
+
+  before(async () => {
+    await createFundedVault({ admin, payees: [payee] });
+  });
+
+  // Test: A due payout larger than the vault balance fails with a custom error
+  it("Cannot claim a payout the vault cannot cover", async () => {
+    await program.methods
+      .schedulePayout(
+        payee.publicKey,
+        new anchor.BN(100 * anchor.web3.LAMPORTS_PER_SOL),
+        new anchor.BN(Math.floor(Date.now() / 1000) + 2),
+        new anchor.BN(3600)
+      )
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+
+    await sleep(4);
+
+    try {
+      await program.methods
+        .claimPayout()
+        .accountsPartial({ user: payee.publicKey, vaultState, vault })
+        .signers([payee])
+        .rpc();
+
+      assert.fail("Expected error when claiming more than the vault holds");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "InsufficientFunds");
+    }
+  });
+});
+
+// Test suite: per-payee epoch spending limits
+describe("devquest-vault epoch limits", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const payee = Keypair.generate();
+  const { vaultState, vault } = deriveVaultAccounts(
+    program.programId,
+    admin.publicKey
+  );
+  const limit = 0.5 * anchor.web3.LAMPORTS_PER_SOL;
+  const duration = 3;
+
+  before(async () => {
+    await createFundedVault({
+      admin,
+      payees: [payee],
+      deposit: 2 * anchor.web3.LAMPORTS_PER_SOL,
+    });
+    await program.methods
+      .setEpochLimit(payee.publicKey, null, new anchor.BN(limit), new anchor.BN(duration))
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+  });
+
+  const status = () =>
+    program.methods
+      .getPayeeStatus(payee.publicKey)
+      .accountsPartial({ vault, vaultState })
+      .view();
+
+  // Test: Withdrawals within the epoch count against the limit
+  it("Tracks spending within the epoch", async () => {
+    await program.methods
+      .withdraw(new anchor.BN(0.4 * anchor.web3.LAMPORTS_PER_SOL))
+      .accountsPartial({ user: payee.publicKey, vaultState, vault })
+      .signers([payee])
+      .rpc();
+
+    const current = await status();
+    assert.equal(current.epochSpent.toNumber(), 0.4 * anchor.web3.LAMPORTS_PER_SOL);
+    assert.equal(current.epochRemaining.toNumber(), 0.1 * anchor.web3.LAMPORTS_PER_SOL);
+
+    try {
+      await program.methods
+        .withdraw(new anchor.BN(0.2 * anchor.web3.LAMPORTS_PER_SOL))
+        .accountsPartial({ user: payee.publicKey, vaultState, vault })
+        .signers([payee])
+        .rpc();
+      assert.fail("Should have failed with EpochSpendingLimitReached");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "EpochSpendingLimitReached");
+    }
+  });
+
+  // Test: Once the epoch has ended the status shows the full allowance before any withdrawal
+  it("Reports the reset allowance after the epoch ends", async () => {
+    await sleep(duration + 1);
+
+    const current = await status();
+    assert.equal(current.epochSpent.toNumber(), 0);
+    assert.equal(current.epochRemaining.toNumber(), limit);
+
+    // The stored amount is still stale until the next withdrawal resets it
+    const state = await program.account.vaultState.fetch(vaultState);
+    // The reset time follows the window a withdrawal would start, not the one that ended
+    const { epochStart } = state.epochLimits[0].spending;
+    assert.isAbove(current.epochResetsAt.toNumber(), epochStart.toNumber() + duration);
+    assert.equal(
+      state.epochLimits[0].spending.spentAmount.toNumber(),
+      0.4 * anchor.web3.LAMPORTS_PER_SOL
+    );
+
+    // The whole allowance can be withdrawn in the new epoch
+    await program.methods
+      .withdraw(new anchor.BN(limit))
+      .accountsPartial({ user: payee.publicKey, vaultState, vault })
+      .signers([payee])
+      .rpc();
+    const after = await status();
+    assert.equal(after.epochRemaining.toNumber(), 0);
+  });
+
+  // Test: Removing a payee frees their limit records instead of keeping the old spending
+  it("Drops a removed payee's limits", async () => {
+    await program.methods
+      .removePayee(payee.publicKey)
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+    let state = await program.account.vaultState.fetch(vaultState);
+    assert.equal(state.epochLimits.length, 0);
+
+    await program.methods
+      .addPayee(payee.publicKey, NO_NAME)
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+    state = await program.account.vaultState.fetch(vaultState);
+    assert.equal(state.epochLimits.length, 0);
+  });
+});
+
+// Test suite: unused epoch allowance rolling into the next epoch
+describe("devquest-vault epoch carryover", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const payee = Keypair.generate();
+  const { vaultState, vault } = deriveVaultAccounts(
+    program.programId,
+    admin.publicKey
+  );
+  const SOL = anchor.web3.LAMPORTS_PER_SOL;
+  const limit = 1 * SOL;
+  const maxCarryover = 0.2 * SOL;
+  const duration = 4;
+
+  before(async () => {
+    await createFundedVault({ admin, payees: [payee], deposit: 3 * SOL });
+    await program.methods
+      .setEpochLimit(payee.publicKey, null, new anchor.BN(limit), new anchor.BN(duration))
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+    await program.methods
+      .setEpochCarryover(payee.publicKey, null, true, new anchor.BN(maxCarryover))
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+  });
+
+  const status = () =>
+    program.methods
+      .getPayeeStatus(payee.publicKey)
+      .accountsPartial({ vault, vaultState })
+      .view();
+
+  const withdraw = (amount: number) =>
+    program.methods
+      .withdraw(new anchor.BN(amount))
+      .accountsPartial({ user: payee.publicKey, vaultState, vault })
+      .signers([payee])
+      .rpc();
+
+  // Test: 30% left unused grants a larger limit next epoch, capped at max_carryover
+  it("Carries unused allowance into the next epoch up to the cap", async () => {
+    await withdraw(0.7 * SOL);
+
+    await sleep(duration + 1);
+    assert.equal((await status()).epochRemaining.toNumber(), limit + maxCarryover);
+
+    // The carried allowance can be spent, but nothing beyond it
+    await withdraw(limit + maxCarryover);
+    try {
+      await withdraw(1);
+      assert.fail("Should have failed with EpochSpendingLimitReached");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "EpochSpendingLimitReached");
+    }
+  });
+
+  // Test: Spending the carried amount leaves nothing to carry, so it does not compound
+  it("Does not compound carried allowance", async () => {
+    await sleep(duration + 1);
+    assert.equal((await status()).epochRemaining.toNumber(), limit);
+  });
+});
+
+// Test suite: scheduled claims counted against epoch limits
+describe("devquest-vault claims within epoch limits", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const payee = Keypair.generate();
+  const { vaultState, vault } = deriveVaultAccounts(
+    program.programId,
+    admin.publicKey
+  );
+  const SOL = anchor.web3.LAMPORTS_PER_SOL;
+  const amount = 0.1 * SOL;
+  const limit = 0.15 * SOL;
+  const interval = 10;
+
+  before(async () => {
+    await createFundedVault({ admin, payees: [payee], deposit: 1 * SOL });
+    await program.methods
+      .setEpochLimit(payee.publicKey, null, new anchor.BN(limit), new anchor.BN(86_400))
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+    await program.methods
+      .setMinInterval(new anchor.BN(interval))
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+    await program.methods
+      .schedulePayout(
+        payee.publicKey,
+        new anchor.BN(amount),
+        new anchor.BN(Math.floor(Date.now() / 1000) + 2),
+        new anchor.BN(interval)
+      )
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+  });
+
+  const claim = () =>
+    program.methods
+      .claimPayout()
+      .accountsPartial({ user: payee.publicKey, vaultState, vault })
+      .signers([payee])
+      .rpc();
+
+  // Test: A fast schedule cannot pay out more than the daily limit
+  it("Rejects a claim that would exceed the epoch limit", async () => {
+    await sleep(3);
+    await claim();
+    let state = await program.account.vaultState.fetch(vaultState);
+    assert.equal(state.epochLimits[0].spending.spentAmount.toNumber(), amount);
+
+    await sleep(interval);
+    try {
+      await claim();
+      assert.fail("Should have failed with EpochSpendingLimitReached");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "EpochSpendingLimitReached");
+    }
+
+    // The rest of the allowance is still available for withdrawals
+    await program.methods
+      .withdraw(new anchor.BN(limit - amount))
+      .accountsPartial({ user: payee.publicKey, vaultState, vault })
+      .signers([payee])
+      .rpc();
+    state = await program.account.vaultState.fetch(vaultState);
+    assert.equal(state.epochLimits[0].spending.spentAmount.toNumber(), limit);
+  });
+});
+
+// Test suite: tracking idle payees
+describe("devquest-vault payee activity", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const active = Keypair.generate();
+  const idle = Keypair.generate();
+  const { vaultState, vault } = deriveVaultAccounts(
+    program.programId,
+    admin.publicKey
+  );
+
+  before(async () => {
+    await createFundedVault({
+      admin,
+      payees: [active, idle],
+      deposit: 1 * anchor.web3.LAMPORTS_PER_SOL,
+    });
+  });
+
+  const inactive = async (threshold: number) =>
+    (
+      await program.methods
+        .getInactivePayees(new anchor.BN(threshold))
+        .accountsPartial({ vault, vaultState })
+        .view()
+    ).map((key) => key.toBase58());
+
+  // Test: Payees that have not withdrawn since being added are reported as idle
+  it("Reports payees idle past the threshold", async () => {
+    await sleep(4);
+    await program.methods
+      .withdraw(new anchor.BN(0.1 * anchor.web3.LAMPORTS_PER_SOL))
+      .accountsPartial({ user: active.publicKey, vaultState, vault })
+      .signers([active])
+      .rpc();
+
+    assert.deepEqual(await inactive(2), [idle.publicKey.toBase58()]);
+    assert.deepEqual(await inactive(3600), []);
+
+    const state = await program.account.vaultState.fetch(vaultState);
+    assert.equal(state.payeeActivity.length, 2);
+  });
+});
+
+// Test suite: admin withdrawals in the running totals
+describe("devquest-vault admin spending", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const payee = Keypair.generate();
+  const { vaultState, vault } = deriveVaultAccounts(
+    program.programId,
+    admin.publicKey
+  );
+  const SOL = anchor.web3.LAMPORTS_PER_SOL;
+
+  before(async () => {
+    await createFundedVault({ admin, payees: [payee], deposit: 1 * SOL });
+  });
+
+  const withdraw = (user: Keypair, amount: number) =>
+    program.methods
+      .withdraw(new anchor.BN(amount))
+      .accountsPartial({ user: user.publicKey, vaultState, vault })
+      .signers([user])
+      .rpc({ commitment: "confirmed" });
+
+  const totals = async () => {
+    const info = await program.methods
+      .getVaultInfo()
+      .accountsPartial({ vaultState, vault })
+      .view();
+    return {
+      total: info.totalWithdrawn.toNumber(),
+      admin: info.adminWithdrawn.toNumber(),
+    };
+  };
+
+  // Test: Admin withdrawals are invisible by default
+  it("Does not count admin withdrawals while tracking is off", async () => {
+    await withdraw(admin, 0.1 * SOL);
+    assert.deepEqual(await totals(), { total: 0, admin: 0 });
+  });
+
+  // Test: With tracking on, admin withdrawals show up in the totals and events
+  it("Counts admin withdrawals once tracking is on", async () => {
+    await program.methods
+      .setTrackAdminSpending(true)
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+
+    const tx = await withdraw(admin, 0.2 * SOL);
+    assert.deepEqual(await totals(), { total: 0.2 * SOL, admin: 0.2 * SOL });
+
+    const event = findEvent(await fetchEvents(program, tx), "WithdrawEvent");
+    assert.ok(event, "WithdrawEvent should be emitted");
+    assert.isTrue(event.data.isAdmin);
+    assert.equal(event.data.totalWithdrawn.toNumber(), 0.2 * SOL);
+
+    // Payee withdrawals add to the same running total
+    await withdraw(payee, 0.1 * SOL);
+    assert.deepEqual(await totals(), { total: 0.3 * SOL, admin: 0.2 * SOL });
+  });
+});
+
+// Test suite: vault-wide spending cap
+describe("devquest-vault global limit", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const payeeA = Keypair.generate();
+  const payeeB = Keypair.generate();
+  const { vaultState, vault } = deriveVaultAccounts(
+    program.programId,
+    admin.publicKey
+  );
+  const SOL = anchor.web3.LAMPORTS_PER_SOL;
+
+  before(async () => {
+    await createFundedVault({
+      admin,
+      payees: [payeeA, payeeB],
+      deposit: 2 * SOL,
+    });
+    await program.methods
+      .setGlobalLimit(new anchor.BN(0.5 * SOL), new anchor.BN(86_400))
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+  });
+
+  const withdraw = (user: Keypair, amount: number) =>
+    program.methods
+      .withdraw(new anchor.BN(amount))
+      .accountsPartial({ user: user.publicKey, vaultState, vault })
+      .signers([user])
+      .rpc();
+
+  // Test: Withdrawals by different payees add up against the same cap
+  it("Rejects combined payee withdrawals above the global cap", async () => {
+    await withdraw(payeeA, 0.3 * SOL);
+    try {
+      await withdraw(payeeB, 0.3 * SOL);
+      assert.fail("Should have failed with GlobalLimitReached");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "GlobalLimitReached");
+    }
+    // What is left of the cap can still be withdrawn
+    await withdraw(payeeB, 0.2 * SOL);
+  });
+
+  // Test: Untracked admin withdrawals are outside the cap
+  it("Does not apply the cap to the admin unless admin spending is tracked", async () => {
+    await withdraw(admin, 0.1 * SOL);
+
+    await program.methods
+      .setTrackAdminSpending(true)
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+    try {
+      await withdraw(admin, 0.1 * SOL);
+      assert.fail("Should have failed with GlobalLimitReached");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "GlobalLimitReached");
+    }
+  });
+
+  // Test: Scheduled claims draw from the same cap as withdrawals
+  it("Rejects a scheduled claim once the global cap is spent", async () => {
+    const interval = 2;
+    await program.methods
+      .setMinInterval(new anchor.BN(interval))
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+    await program.methods
+      .schedulePayout(
+        payeeA.publicKey,
+        new anchor.BN(0.1 * SOL),
+        new anchor.BN(Math.floor(Date.now() / 1000) + 1),
+        new anchor.BN(interval)
+      )
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+    await sleep(2);
+
+    try {
+      await program.methods
+        .claimPayout()
+        .accountsPartial({ user: payeeA.publicKey, vaultState, vault })
+        .signers([payeeA])
+        .rpc();
+      assert.fail("Should have failed with GlobalLimitReached");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "GlobalLimitReached");
+    }
+  });
+});
+
+// Test suite: compliance memos on withdrawals
+describe("devquest-vault withdrawal memos", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const payee = Keypair.generate();
+  const { vaultState, vault } = deriveVaultAccounts(
+    program.programId,
+    admin.publicKey
+  );
+  const amount = 0.1 * anchor.web3.LAMPORTS_PER_SOL;
+
+  // Memos are fixed 64-byte arrays, zero padded
+  const encodeMemo = (text: string) => {
+    const bytes = Buffer.alloc(64);
+    bytes.write(text, "utf8");
+    return Array.from(bytes);
+  };
+
+  before(async () => {
+    await createFundedVault({
+      admin,
+      payees: [payee],
+      deposit: 1 * anchor.web3.LAMPORTS_PER_SOL,
+    });
+    await program.methods
+      .setRequireWithdrawMemo(true)
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+  });
+
+  // Test: Plain withdrawals are blocked while memos are required
+  it("Rejects a plain withdrawal in memo mode", async () => {
+    try {
+      await program.methods
+        .withdraw(new anchor.BN(amount))
+        .accountsPartial({ user: payee.publicKey, vaultState, vault })
+        .signers([payee])
+        .rpc();
+      assert.fail("Should have failed with MemoRequired");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "MemoRequired");
+    }
+  });
+
+  // Test: An empty memo does not count as a reason code
+  it("Rejects an all-zero memo", async () => {
+    try {
+      await program.methods
+        .withdrawWithMemo(new anchor.BN(amount), encodeMemo(""))
+        .accountsPartial({ user: payee.publicKey, vaultState, vault })
+        .signers([payee])
+        .rpc();
+      assert.fail("Should have failed with MemoRequired");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "MemoRequired");
+    }
+  });
+
+  // Test: The memo variant succeeds and records the memo in the event
+  it("Withdraws with a memo", async () => {
+    const memo = encodeMemo("GRANT-2024-017");
+    const tx = await program.methods
+      .withdrawWithMemo(new anchor.BN(amount), memo)
+      .accountsPartial({ user: payee.publicKey, vaultState, vault })
+      .signers([payee])
+      .rpc({ commitment: "confirmed" });
+
+    const event = findEvent(await fetchEvents(program, tx), "WithdrawEvent");
+    assert.ok(event, "WithdrawEvent should be emitted");
+    assert.equal(event.data.amount.toNumber(), amount);
+    assert.deepEqual(Array.from(event.data.memo), memo);
+  });
+
+  // Test: Delayed withdrawals need a memo too, which is kept until execution
+  it("Carries the memo through a delayed withdrawal", async () => {
+    const delay = 2;
+    await program.methods
+      .setWithdrawalDelay(new anchor.BN(amount / 2), new anchor.BN(delay))
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+    try {
+      await program.methods
+        .requestWithdrawal(new anchor.BN(amount))
+        .accountsPartial({ user: payee.publicKey, vaultState, vault })
+        .signers([payee])
+        .rpc();
+      assert.fail("Should have failed with MemoRequired");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "MemoRequired");
+    }
+
+    const memo = encodeMemo("PAYROLL-2024-09");
+    await program.methods
+      .requestWithdrawalWithMemo(new anchor.BN(amount), memo)
+      .accountsPartial({ user: payee.publicKey, vaultState, vault })
+      .signers([payee])
+      .rpc();
+    await sleep(delay + 1);
+    const tx = await program.methods
+      .executeWithdrawal()
+      .accountsPartial({ user: payee.publicKey, vaultState, vault })
+      .signers([payee])
+      .rpc({ commitment: "confirmed" });
+
+    const event = findEvent(await fetchEvents(program, tx), "WithdrawEvent");
+    assert.ok(event, "WithdrawEvent should be emitted");
+    assert.equal(event.data.amount.toNumber(), amount);
+    assert.deepEqual(Array.from(event.data.memo), memo);
+  });
+});
+
+// Test suite: vault name validation
+describe("devquest-vault metadata", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const { vaultState, vault } = deriveVaultAccounts(
+    program.programId,
+    admin.publicKey
+  );
+
+  before(async () => {
+    await airdrop(provider, admin.publicKey, 1);
+  });
+
+  // Test: Names that are not valid UTF-8 are rejected
+  it("Rejects a non-UTF-8 name", async () => {
+    const name = NO_NAME.slice();
+    name[0] = 0xff;
+    name[1] = 0xfe;
+    try {
+      await program.methods
+        .initialize(name, new anchor.BN(0))
+        .accountsPartial({ user: admin.publicKey, vaultState, vault })
+        .signers([admin])
+        .rpc();
+      assert.fail("Should have failed with InvalidVaultName");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "InvalidVaultName");
+    }
+  });
+
+  // Test: A name filling all 32 bytes is accepted unchanged
+  it("Accepts a full-length name", async () => {
+    const text = "a".repeat(32);
+    await program.methods
+      .initialize(encodeName(text), new anchor.BN(0))
+      .accountsPartial({ user: admin.publicKey, vaultState, vault })
+      .signers([admin])
+      .rpc();
+
+    const state = await program.account.vaultState.fetch(vaultState);
+    assert.equal(decodeName(state.name), text);
+  });
+});
+
+// Test suite: catch-up claims limited by the vault balance
+describe("devquest-vault partial catch-up", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const payee = Keypair.generate();
+  const { vaultState, vault } = deriveVaultAccounts(
+    program.programId,
+    admin.publicKey
+  );
+  const amount = 0.1 * anchor.web3.LAMPORTS_PER_SOL;
+  const interval = 4;
+  let startTime: number;
+
+  before(async () => {
+    await createFundedVault({
+      admin,
+      payees: [payee],
+      deposit: 0.25 * anchor.web3.LAMPORTS_PER_SOL,
+    });
+    await program.methods
+      .setMinInterval(new anchor.BN(interval))
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+
+    startTime = Math.floor(Date.now() / 1000) + 1;
+    await program.methods
+      .schedulePayout(
+        payee.publicKey,
+        new anchor.BN(amount),
+        new anchor.BN(startTime),
+        new anchor.BN(interval)
+      )
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+  });
+
+  // Test: With three periods due and funds for two, only two are paid
+  it("Pays only the periods the vault can cover", async () => {
+    // Wait until three periods have come due
+    await sleep(2 * interval + 3);
+
+    const payeeBefore = await provider.connection.getBalance(payee.publicKey);
+    await program.methods
+      .claimPayout()
+      .accountsPartial({ user: payee.publicKey, vaultState, vault })
+      .signers([payee])
+      .rpc();
+    const payeeAfter = await provider.connection.getBalance(payee.publicKey);
+
+    // Two periods paid, less the transaction fee
+    assert.approximately(payeeAfter - payeeBefore, 2 * amount, 10_000);
+
+    // Time advances by exactly the two periods paid, leaving the third due
+    const state = await program.account.vaultState.fetch(vaultState);
+    assert.equal(
+      state.payoutSchedules[0].nextPayoutTime.toNumber(),
+      startTime + 2 * interval
+    );
+  });
+
+  // Test: The remaining period stays claimable but cannot be paid yet
+  it("Fails with InsufficientFunds when no whole period is affordable", async () => {
+    try {
+      await program.methods
+        .claimPayout()
+        .accountsPartial({ user: payee.publicKey, vaultState, vault })
+        .signers([payee])
+        .rpc();
+      assert.fail("Expected error when the vault cannot cover a single period");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "InsufficientFunds");
+    }
+  });
+});
+
+// Test suite: capping what a single claim can pay
+describe("devquest-vault claim cap", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const payee = Keypair.generate();
+  const { vaultState, vault } = deriveVaultAccounts(
+    program.programId,
+    admin.publicKey
+  );
+  const SOL = anchor.web3.LAMPORTS_PER_SOL;
+  const amount = 0.1 * SOL;
+  const cap = 0.15 * SOL;
+  const interval = 10;
+  let startTime: number;
+
+  before(async () => {
+    await createFundedVault({ admin, payees: [payee], deposit: 1 * SOL });
+    await program.methods
+      .setMinInterval(new anchor.BN(interval))
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+    startTime = Math.floor(Date.now() / 1000) + 2;
+    await program.methods
+      .schedulePayout(
+        payee.publicKey,
+        new anchor.BN(amount),
+        new anchor.BN(startTime),
+        new anchor.BN(interval)
+      )
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+    await program.methods
+      .setMaxPerClaim(payee.publicKey, new anchor.BN(cap))
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+  });
+
+  const claim = async () => {
+    const tx = await program.methods
+      .claimPayout()
+      .accountsPartial({ user: payee.publicKey, vaultState, vault })
+      .signers([payee])
+      .rpc({ commitment: "confirmed" });
+    return findEvent(await fetchEvents(program, tx), "PayoutClaimedEvent");
+  };
+
+  const nextClaimTime = (who: anchor.web3.PublicKey) =>
+    program.methods
+      .getNextClaimTime(who)
+      .accountsPartial({ vaultState, vault })
+      .view();
+
+  // Test: The countdown targets the schedule's next payout time
+  it("Reports the next claim time", async () => {
+    const next = await nextClaimTime(payee.publicKey);
+    assert.equal(next.toNumber(), startTime);
+    // Payees without a schedule get no countdown rather than an error
+    assert.isNull(await nextClaimTime(admin.publicKey));
+  });
+
+  // Test: A catch-up larger than the cap is drawn over several claims
+  it("Caps a catch-up claim and defers the rest", async () => {
+    await sleep(2 + interval + 1);
+
+    const first = await claim();
+    assert.ok(first, "PayoutClaimedEvent should be emitted");
+    assert.equal(first.data.amount.toNumber(), cap);
+    assert.equal(first.data.deferred.toNumber(), 2 * amount - cap);
+
+    // The deferred part is owed right away, without waiting for the next period
+    const now = (await nextClaimTime(payee.publicKey)).toNumber();
+    assert.isBelow(now, startTime + 2 * interval);
+    const second = await claim();
+    assert.equal(second.data.amount.toNumber(), 2 * amount - cap);
+    assert.equal(second.data.deferred.toNumber(), 0);
+
+    try {
+      await claim();
+      assert.fail("Should have failed with PayoutTimeNotReached");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "PayoutTimeNotReached");
+    }
+  });
+});
+
+// Test suite: payouts split between several payees by weight
+describe("devquest-vault split payouts", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const recipients = [Keypair.generate(), Keypair.generate(), Keypair.generate()];
+  const weights = [6000, 3000, 1000];
+  const { vaultState, vault } = deriveVaultAccounts(
+    program.programId,
+    admin.publicKey
+  );
+  const amount = 1 * anchor.web3.LAMPORTS_PER_SOL;
+
+  const splitsFor = (bps: number[]) =>
+    bps.map((weightBps, i) => ({ recipient: recipients[i].publicKey, weightBps }));
+  const remainingAccounts = (keypairs: Keypair[]) =>
+    keypairs.map((k) => ({ pubkey: k.publicKey, isWritable: true, isSigner: false }));
+
+  before(async () => {
+    await createFundedVault({
+      admin,
+      payees: recipients,
+      deposit: 3 * anchor.web3.LAMPORTS_PER_SOL,
+    });
+  });
+
+  // Test: Weights must add up to exactly 10000 bps
+  it("Rejects weights that do not sum to 10000", async () => {
+    try {
+      await program.methods
+        .scheduleSplitPayout(
+          new anchor.BN(amount),
+          new anchor.BN(Math.floor(Date.now() / 1000) + 60),
+          new anchor.BN(3600),
+          splitsFor([6000, 3000])
+        )
+        .accountsPartial({ user: admin.publicKey, vaultState })
+        .signers([admin])
+        .rpc();
+      assert.fail("Should have failed with InvalidSplit");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "InvalidSplit");
+    }
+  });
+
+  // Test: A recipient cannot appear twice
+  it("Rejects duplicate recipients", async () => {
+    try {
+      await program.methods
+        .scheduleSplitPayout(
+          new anchor.BN(amount),
+          new anchor.BN(Math.floor(Date.now() / 1000) + 60),
+          new anchor.BN(3600),
+          [
+            { recipient: recipients[0].publicKey, weightBps: 5000 },
+            { recipient: recipients[0].publicKey, weightBps: 5000 },
+          ]
+        )
+        .accountsPartial({ user: admin.publicKey, vaultState })
+        .signers([admin])
+        .rpc();
+      assert.fail("Should have failed with InvalidSplit");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "InvalidSplit");
+    }
+  });
+
+  // Test: A 60/30/10 split schedule pays each recipient its share
+  it("Distributes a payout by weight", async () => {
+    await program.methods
+      .scheduleSplitPayout(
+        new anchor.BN(amount),
+        new anchor.BN(Math.floor(Date.now() / 1000) + 2),
+        new anchor.BN(3600),
+        splitsFor(weights)
+      )
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+
+    await sleep(3);
+
+    // Recipients passed out of order are rejected
+    try {
+      await program.methods
+        .claimSplitPayout(0)
+        .accountsPartial({ user: admin.publicKey, vaultState, vault })
+        .remainingAccounts(remainingAccounts([...recipients].reverse()))
+        .signers([admin])
+        .rpc();
+      assert.fail("Should have failed with InvalidSplit");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "InvalidSplit");
+    }
+
+    const activityBefore = (await program.account.vaultState.fetch(vaultState)).payeeActivity;
+    const before = await Promise.all(
+      recipients.map((r) => provider.connection.getBalance(r.publicKey))
+    );
+    await program.methods
+      .claimSplitPayout(0)
+      .accountsPartial({ user: admin.publicKey, vaultState, vault })
+      .remainingAccounts(remainingAccounts(recipients))
+      .signers([admin])
+      .rpc();
+    const after = await Promise.all(
+      recipients.map((r) => provider.connection.getBalance(r.publicKey))
+    );
+
+    weights.forEach((bps, i) => {
+      assert.equal(after[i] - before[i], (amount * bps) / 10_000);
+    });
+
+    // Every recipient is a payee, so the claim counts as activity for each of them
+    const lastActivity = (activities, key: anchor.web3.PublicKey) =>
+      activities.find((a) => a.payee.equals(key)).lastActivity.toNumber();
+    const { payeeActivity } = await program.account.vaultState.fetch(vaultState);
+    for (const recipient of recipients) {
+      assert.isAbove(
+        lastActivity(payeeActivity, recipient.publicKey),
+        lastActivity(activityBefore, recipient.publicKey)
+      );
+    }
+
+    // The next period is not due yet
+    try {
+      await program.methods
+        .claimSplitPayout(0)
+        .accountsPartial({ user: admin.publicKey, vaultState, vault })
+        .remainingAccounts(remainingAccounts(recipients))
+        .signers([admin])
+        .rpc();
+      assert.fail("Should have failed with PayoutTimeNotReached");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "PayoutTimeNotReached");
+    }
+  });
+
+  // Test: Uneven weights leave no dust behind; the largest weight absorbs it
+  it("Loses no lamports to rounding", async () => {
+    const unevenAmount = 1_000_000_007;
+    const uneven = [3333, 3333, 3334];
+    await program.methods
+      .scheduleSplitPayout(
+        new anchor.BN(unevenAmount),
+        new anchor.BN(Math.floor(Date.now() / 1000) + 2),
+        new anchor.BN(3600),
+        splitsFor(uneven)
+      )
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+
+    await sleep(3);
+
+    const vaultBefore = await provider.connection.getBalance(vault);
+    const before = await Promise.all(
+      recipients.map((r) => provider.connection.getBalance(r.publicKey))
+    );
+    await program.methods
+      .claimSplitPayout(1)
+      .accountsPartial({ user: admin.publicKey, vaultState, vault })
+      .remainingAccounts(remainingAccounts(recipients))
+      .signers([admin])
+      .rpc();
+    const vaultAfter = await provider.connection.getBalance(vault);
+    const after = await Promise.all(
+      recipients.map((r) => provider.connection.getBalance(r.publicKey))
+    );
+
+    const received = after.map((balance, i) => balance - before[i]);
+    assert.equal(received[0], Math.floor((unevenAmount * 3333) / 10_000));
+    assert.equal(received[1], Math.floor((unevenAmount * 3333) / 10_000));
+    assert.equal(received[2], Math.floor((unevenAmount * 3334) / 10_000) + 1);
+    assert.equal(received.reduce((a, b) => a + b, 0), unevenAmount);
+    assert.equal(vaultBefore - vaultAfter, unevenAmount);
+  });
+
+  // Test: A recipient's share is held to their epoch limit like any other payment
+  it("Rejects a split claim above a recipient's epoch limit", async () => {
+    await program.methods
+      .setEpochLimit(recipients[2].publicKey, null, new anchor.BN(1_000), new anchor.BN(86_400))
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+    await program.methods
+      .scheduleSplitPayout(
+        new anchor.BN(0.1 * anchor.web3.LAMPORTS_PER_SOL),
+        new anchor.BN(Math.floor(Date.now() / 1000) + 2),
+        new anchor.BN(3600),
+        splitsFor(weights)
+      )
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+
+    await sleep(3);
+
+    try {
+      await program.methods
+        .claimSplitPayout(2)
+        .accountsPartial({ user: admin.publicKey, vaultState, vault })
+        .remainingAccounts(remainingAccounts(recipients))
+        .signers([admin])
+        .rpc();
+      assert.fail("Should have failed with EpochSpendingLimitReached");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "EpochSpendingLimitReached");
+    }
+  });
+
+  // Test: A split schedule has no payee to cancel by, so it is cancelled by index
+  it("Cancels a split schedule by index", async () => {
+    await program.methods
+      .cancelSplitPayout(1)
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+    const state = await program.account.vaultState.fetch(vaultState);
+    assert.isFalse(state.payoutSchedules[1].isActive);
+    assert.isTrue(state.payoutSchedules[2].isActive);
+
+    // The cancelled schedule can no longer be claimed or cancelled again
+    try {
+      await program.methods
+        .claimSplitPayout(1)
+        .accountsPartial({ user: admin.publicKey, vaultState, vault })
+        .remainingAccounts(remainingAccounts(recipients))
+        .signers([admin])
+        .rpc();
+      assert.fail("Should have failed with ScheduleNotFound");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "ScheduleNotFound");
+    }
+    try {
+      await program.methods
+        .cancelSplitPayout(1)
+        .accountsPartial({ user: admin.publicKey, vaultState })
+        .signers([admin])
+        .rpc();
+      assert.fail("Should have failed with ScheduleNotFound");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "ScheduleNotFound");
+    }
+  });
+});
+
+// Test suite: lowering a payout rate with periods already due
+describe("devquest-vault rate changes", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const payee = Keypair.generate();
+  const { vaultState, vault } = deriveVaultAccounts(
+    program.programId,
+    admin.publicKey
+  );
+  const oldAmount = 0.1 * anchor.web3.LAMPORTS_PER_SOL;
+  const newAmount = 0.05 * anchor.web3.LAMPORTS_PER_SOL;
+  const interval = 3600;
+  let startTime: number;
+
+  before(async () => {
+    await createFundedVault({
+      admin,
+      payees: [payee],
+      deposit: 1 * anchor.web3.LAMPORTS_PER_SOL,
+    });
+    startTime = Math.floor(Date.now() / 1000) + 1;
+    await program.methods
+      .schedulePayout(
+        payee.publicKey,
+        new anchor.BN(oldAmount),
+        new anchor.BN(startTime),
+        new anchor.BN(interval)
+      )
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+  });
+
+  // Test: A period that came due before the update is paid at the old rate
+  it("Pays due periods at the rate in effect when they came due", async () => {
+    await sleep(3);
+
+    await program.methods
+      .updatePayout(payee.publicKey, { fixed: { 0: new anchor.BN(newAmount) } }, new anchor.BN(interval))
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+
+    // The due period is locked in and the schedule moves on to the next one
+    let state = await program.account.vaultState.fetch(vaultState);
+    assert.equal(state.payoutSchedules[0].accrued.toNumber(), oldAmount);
+    assert.equal(
+      state.payoutSchedules[0].nextPayoutTime.toNumber(),
+      startTime + interval
+    );
+
+    const payeeBefore = await provider.connection.getBalance(payee.publicKey);
+    await program.methods
+      .claimPayout()
+      .accountsPartial({ user: payee.publicKey, vaultState, vault })
+      .signers([payee])
+      .rpc();
+    const payeeAfter = await provider.connection.getBalance(payee.publicKey);
+    assert.approximately(payeeAfter - payeeBefore, oldAmount, 10_000);
+
+    state = await program.account.vaultState.fetch(vaultState);
+    assert.equal(state.payoutSchedules[0].accrued.toNumber(), 0);
+
+    // Nothing more is owed until the next period, which pays the new rate
+    try {
+      await program.methods
+        .claimPayout()
+        .accountsPartial({ user: payee.publicKey, vaultState, vault })
+        .signers([payee])
+        .rpc();
+      assert.fail("Should have failed with PayoutTimeNotReached");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "PayoutTimeNotReached");
+    }
+  });
+});
+
+// Test suite: moving a schedule to another payee
+describe("devquest-vault reassign payouts", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const original = Keypair.generate();
+  const replacement = Keypair.generate();
+  const other = Keypair.generate();
+  const { vaultState, vault } = deriveVaultAccounts(
+    program.programId,
+    admin.publicKey
+  );
+  const amount = 0.1 * anchor.web3.LAMPORTS_PER_SOL;
+  let startTime: number;
+
+  before(async () => {
+    await createFundedVault({
+      admin,
+      payees: [original, replacement, other],
+      deposit: 1 * anchor.web3.LAMPORTS_PER_SOL,
+    });
+
+    startTime = Math.floor(Date.now() / 1000) + 3;
+    await program.methods
+      .schedulePayout(
+        original.publicKey,
+        new anchor.BN(amount),
+        new anchor.BN(startTime),
+        new anchor.BN(3600)
+      )
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+  });
+
+  const reassign = (from: anchor.web3.PublicKey, to: anchor.web3.PublicKey) =>
+    program.methods
+      .reassignPayout(from, to)
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+
+  // Test: The new payee must already be registered
+  it("Rejects reassigning to an unknown payee", async () => {
+    try {
+      await reassign(original.publicKey, Keypair.generate().publicKey);
+      assert.fail("Should have failed with PayeeNotFound");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "PayeeNotFound");
+    }
+  });
+
+  // Test: The old payee must have an active schedule
+  it("Rejects reassigning from a payee without a schedule", async () => {
+    try {
+      await reassign(other.publicKey, replacement.publicKey);
+      assert.fail("Should have failed with ScheduleNotFound");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "ScheduleNotFound");
+    }
+  });
+
+  // Test: The new payee claims on the original timeline
+  it("Moves the schedule with its timeline intact", async () => {
+    await reassign(original.publicKey, replacement.publicKey);
+
+    const state = await program.account.vaultState.fetch(vaultState);
+    assert.ok(state.payoutSchedules[0].payee.equals(replacement.publicKey));
+    assert.equal(state.payoutSchedules[0].nextPayoutTime.toNumber(), startTime);
+
+    await sleep(4);
+    const before = await provider.connection.getBalance(replacement.publicKey);
+    await program.methods
+      .claimPayout()
+      .accountsPartial({ user: replacement.publicKey, vaultState, vault })
+      .signers([replacement])
+      .rpc();
+    const after = await provider.connection.getBalance(replacement.publicKey);
+    assert.approximately(after - before, amount, 10_000);
+
+    try {
+      await program.methods
+        .claimPayout()
+        .accountsPartial({ user: original.publicKey, vaultState, vault })
+        .signers([original])
+        .rpc();
+      assert.fail("Should have failed with NoScheduleForPayee");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "NoScheduleForPayee");
+    }
+  });
+});
+
+// Test suite: redirecting unclaimed payouts to a fallback recipient
+describe("devquest-vault escheat", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const payee = Keypair.generate();
+  const fallback = Keypair.generate();
+  const { vaultState, vault } = deriveVaultAccounts(
+    program.programId,
+    admin.publicKey
+  );
+  const amount = 0.1 * anchor.web3.LAMPORTS_PER_SOL;
+  const gracePeriod = 3;
+
+  before(async () => {
+    await createFundedVault({
+      admin,
+      payees: [payee],
+      deposit: 1 * anchor.web3.LAMPORTS_PER_SOL,
+    });
+    await program.methods
+      .schedulePayout(
+        payee.publicKey,
+        new anchor.BN(amount),
+        new anchor.BN(Math.floor(Date.now() / 1000) + 2),
+        new anchor.BN(3600)
+      )
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+  });
+
+  const escheat = (fallbackAccount: anchor.web3.PublicKey) =>
+    program.methods
+      .escheat(payee.publicKey)
+      .accountsPartial({ user: admin.publicKey, fallbackAccount, vaultState, vault })
+      .signers([admin])
+      .rpc({ commitment: "confirmed" });
+
+  // Test: Nothing is redirected unless a fallback was configured
+  it("Rejects escheat without a fallback recipient", async () => {
+    try {
+      await escheat(fallback.publicKey);
+      assert.fail("Should have failed with NoFallbackRecipient");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "NoFallbackRecipient");
+    }
+
+    await program.methods
+      .setPayoutFallback(
+        payee.publicKey,
+        fallback.publicKey,
+        new anchor.BN(gracePeriod)
+      )
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+  });
+
+  // Test: The payee keeps the payout until the grace period has passed
+  it("Rejects escheat before the grace period has elapsed", async () => {
+    try {
+      await escheat(fallback.publicKey);
+      assert.fail("Should have failed with GracePeriodNotElapsed");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "GracePeriodNotElapsed");
+    }
+  });
+
+  // Test: The funds can only go to the configured fallback
+  it("Rejects a different recipient account", async () => {
+    await sleep(2 + gracePeriod + 1);
+    try {
+      await escheat(Keypair.generate().publicKey);
+      assert.fail("Should have failed with InvalidBeneficiary");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "InvalidBeneficiary");
+    }
+  });
+
+  // Test: The due payout goes to the fallback and the payee has nothing left to claim
+  it("Redirects the unclaimed payout to the fallback recipient", async () => {
+    const tx = await escheat(fallback.publicKey);
+
+    assert.equal(await provider.connection.getBalance(fallback.publicKey), amount);
+    const escheated = findEvent(
+      await fetchEvents(program, tx),
+      "PayoutEscheatedEvent"
+    );
+    assert.ok(escheated, "PayoutEscheatedEvent should be emitted");
+    assert.ok(escheated.data.payee.equals(payee.publicKey));
+    assert.ok(escheated.data.recipient.equals(fallback.publicKey));
+    assert.equal(escheated.data.amount.toNumber(), amount);
+
+    try {
+      await escheat(fallback.publicKey);
+      assert.fail("Should have failed with GracePeriodNotElapsed");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "GracePeriodNotElapsed");
+    }
+  });
+});
+
+// Test suite: linear streams vesting every second
+describe("devquest-vault streams", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const payee = Keypair.generate();
+  const { vaultState, vault } = deriveVaultAccounts(
+    program.programId,
+    admin.publicKey
+  );
+  const total = 1 * anchor.web3.LAMPORTS_PER_SOL;
+  const duration = 10;
+  let startTime: number;
+
+  before(async () => {
+    await createFundedVault({
+      admin,
+      payees: [payee],
+      deposit: 2 * anchor.web3.LAMPORTS_PER_SOL,
+    });
+    await program.methods
+      .setMinInterval(new anchor.BN(duration))
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+
+    startTime = Math.floor(Date.now() / 1000) + 3;
+    await program.methods
+      .scheduleStream(
+        payee.publicKey,
+        new anchor.BN(total),
+        new anchor.BN(startTime),
+        new anchor.BN(startTime + duration)
+      )
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+  });
+
+  const claim = () =>
+    program.methods
+      .claimStream()
+      .accountsPartial({ user: payee.publicKey, vaultState, vault })
+      .signers([payee])
+      .rpc();
+
+  const stream = async () => {
+    const state = await program.account.vaultState.fetch(vaultState);
+    return state.payoutSchedules[0];
+  };
+
+  // Test: Nothing has vested before the start time
+  it("Pays nothing at 0%", async () => {
+    try {
+      await claim();
+      assert.fail("Should have failed with PayoutTimeNotReached");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "PayoutTimeNotReached");
+    }
+  });
+
+  // Test: Midway through, the vested part is paid and recorded
+  it("Pays the vested part midway", async () => {
+    await sleep(startTime + duration / 2 - Date.now() / 1000);
+    const before = await provider.connection.getBalance(payee.publicKey);
+    await claim();
+
+    const claimed = (await stream()).kind.stream.claimedSoFar.toNumber();
+    assert.isAbove(claimed, 0);
+    assert.isBelow(claimed, total);
+    const after = await provider.connection.getBalance(payee.publicKey);
+    // The payee also paid the transaction fee
+    assert.approximately(after - before, claimed, 10_000);
+  });
+
+  // Test: After the end time the stream pays up to its total and finishes
+  it("Clamps to the total past 100%", async () => {
+    await sleep(startTime + duration + 1 - Date.now() / 1000);
+    await claim();
+
+    const schedule = await stream();
+    assert.equal(schedule.kind.stream.claimedSoFar.toNumber(), total);
+    assert.isFalse(schedule.isActive);
+
+    try {
+      await claim();
+      assert.fail("Should have failed with NoScheduleForPayee");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "NoScheduleForPayee");
+    }
+  });
+});
+
+// Test suite: whitelist-only deposits
+describe("devquest-vault restricted deposits", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const depositor = Keypair.generate();
+  const outsider = Keypair.generate();
+  const { vaultState, vault } = deriveVaultAccounts(
+    program.programId,
+    admin.publicKey
+  );
+  const amount = new anchor.BN(0.1 * anchor.web3.LAMPORTS_PER_SOL);
+
+  before(async () => {
+    await createFundedVault({ admin });
+    await airdrop(provider, depositor.publicKey, 1);
+    await airdrop(provider, outsider.publicKey, 1);
+
+    await program.methods
+      .setRestrictedDeposits(true)
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+    await program.methods
+      .addDepositor(depositor.publicKey)
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+  });
+
+  // Test: Non-whitelisted users cannot deposit while deposits are restricted
+  it("Rejects deposits from non-whitelisted users", async () => {
+    try {
+      await program.methods
+        .deposit(amount)
+        .accountsPartial({ user: outsider.publicKey, vaultState, vault })
+        .signers([outsider])
+        .rpc();
+
+      assert.fail("Expected error when a non-whitelisted user deposits");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "UnauthorizedDepositor");
+    }
+  });
+
+  // Test: Whitelisted depositors and the admin can deposit
+  it("Accepts deposits from the admin and whitelisted depositors", async () => {
+    await program.methods
+      .deposit(amount)
+      .accountsPartial({ user: depositor.publicKey, vaultState, vault })
+      .signers([depositor])
+      .rpc();
+    await program.methods
+      .deposit(amount)
+      .accountsPartial({ user: admin.publicKey, vaultState, vault })
+      .signers([admin])
+      .rpc();
+  });
+
+  // Test: Anyone can deposit once restrictions are lifted
+  it("Accepts any deposit when unrestricted", async () => {
+    await program.methods
+      .setRestrictedDeposits(false)
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+    await program.methods
+      .deposit(amount)
+      .accountsPartial({ user: outsider.publicKey, vaultState, vault })
+      .signers([outsider])
+      .rpc();
+  });
+});
+
+// Test suite: skimming free funds back to the admin
+describe("devquest-vault skim", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const payee = Keypair.generate();
+  const { vaultState, vault } = deriveVaultAccounts(
+    program.programId,
+    admin.publicKey
+  );
+  const scheduled = 0.5 * anchor.web3.LAMPORTS_PER_SOL;
+
+  before(async () => {
+    await createFundedVault({
+      admin,
+      payees: [payee],
+      deposit: 2 * anchor.web3.LAMPORTS_PER_SOL,
+    });
+    await program.methods
+      .schedulePayout(
+        payee.publicKey,
+        new anchor.BN(scheduled),
+        new anchor.BN(Math.floor(Date.now() / 1000) + 3600),
+        new anchor.BN(3600)
+      )
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+  });
+
+  // Test: Skim leaves the rent floor and the next scheduled payout in the vault
+  it("Skims everything except rent and pending obligations", async () => {
+    const rentExempt =
+      await provider.connection.getMinimumBalanceForRentExemption(0);
+    const vaultBefore = await provider.connection.getBalance(vault);
+
+    // Skim writes the vault state, so its return value is read from the transaction logs
+    const signature = await program.methods
+      .skim()
+      .accountsPartial({ user: admin.publicKey, vaultState, vault })
+      .signers([admin])
+      .rpc({ commitment: "confirmed" });
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const prefix = `Program return: ${program.programId.toBase58()} `;
+    const returned = tx.meta.logMessages.find((log) => log.startsWith(prefix));
+    const skimmed = new anchor.BN(Buffer.from(returned.slice(prefix.length), "base64"), "le");
+    assert.equal(skimmed.toNumber(), vaultBefore - rentExempt - scheduled);
+
+    assert.equal(
+      await provider.connection.getBalance(vault),
+      rentExempt + scheduled
+    );
+  });
+
+  // Test: Only the admin can skim
+  it("Only admin can skim", async () => {
+    try {
+      await program.methods
+        .skim()
+        .accountsPartial({ user: payee.publicKey, vaultState, vault })
+        .signers([payee])
+        .rpc();
+
+      assert.fail("Expected error when a non-admin skims");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "UnauthorizedAdmin");
+    }
+  });
+
+  // Test: With admin spending tracked, a skim is held to the global limit and counted
+  it("Treats a skim as a tracked admin withdrawal", async () => {
+    const extra = 0.3 * anchor.web3.LAMPORTS_PER_SOL;
+    const setGlobalLimit = (limit: number) =>
+      program.methods
+        .setGlobalLimit(new anchor.BN(limit), new anchor.BN(86_400))
+        .accountsPartial({ user: admin.publicKey, vaultState })
+        .signers([admin])
+        .rpc();
+    await program.methods
+      .setTrackAdminSpending(true)
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+    await program.methods
+      .deposit(new anchor.BN(extra))
+      .accountsPartial({ user: admin.publicKey, vaultState, vault })
+      .signers([admin])
+      .rpc();
+
+    await setGlobalLimit(extra - 1);
+    try {
+      await program.methods
+        .skim()
+        .accountsPartial({ user: admin.publicKey, vaultState, vault })
+        .signers([admin])
+        .rpc();
+      assert.fail("Should have failed with GlobalLimitReached");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "GlobalLimitReached");
+    }
+
+    await setGlobalLimit(extra);
+    await program.methods
+      .skim()
+      .accountsPartial({ user: admin.publicKey, vaultState, vault })
+      .signers([admin])
+      .rpc();
+    const state = await program.account.vaultState.fetch(vaultState);
+    assert.equal(state.adminWithdrawn.toNumber(), extra);
+    assert.equal(state.totalWithdrawn.toNumber(), extra);
+    assert.equal(state.globalEpoch.spentAmount.toNumber(), extra);
+  });
+});
+
+// Test suite: vesting schedules with a cliff
+describe("devquest-vault minimum interval", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const payee = Keypair.generate();
+  const { vaultState, vault } = deriveVaultAccounts(
+    program.programId,
+    admin.publicKey
+  );
+  const amount = new anchor.BN(0.1 * anchor.web3.LAMPORTS_PER_SOL);
+
+  before(async () => {
+    await createFundedVault({ admin, payees: [payee] });
+  });
+
+  // Test: New vaults start with the default floor
+  it("Defaults the minimum interval to one hour", async () => {
+    const state = await program.account.vaultState.fetch(vaultState);
+    assert.equal(state.minInterval.toNumber(), 3600);
+  });
+
+  // Test: An interval below the floor is rejected
+  it("Rejects a 1-second interval", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    try {
+      await program.methods
+        .schedulePayout(
+          payee.publicKey,
+          amount,
+          new anchor.BN(now + 60),
+          new anchor.BN(1)
+        )
+        .accountsPartial({ user: admin.publicKey, vaultState })
+        .signers([admin])
+        .rpc();
+      assert.fail("Should have failed with InvalidPayoutSchedule");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "InvalidPayoutSchedule");
+    }
+  });
+
+  // Test: An interval equal to the floor is accepted
+  it("Accepts an interval at the floor", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    await program.methods
+      .schedulePayout(
+        payee.publicKey,
+        amount,
+        new anchor.BN(now + 60),
+        new anchor.BN(3600)
+      )
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+
+    const state = await program.account.vaultState.fetch(vaultState);
+    assert.equal(state.payoutSchedules[0].interval.toNumber(), 3600);
+  });
+
+  // Test: Updating a schedule below the floor is rejected too
+  it("Rejects updating to an interval below the floor", async () => {
+    try {
+      await program.methods
+        .updatePayout(payee.publicKey, { fixed: { 0: amount } }, new anchor.BN(1))
+        .accountsPartial({ user: admin.publicKey, vaultState })
+        .signers([admin])
+        .rpc();
+      assert.fail("Should have failed with InvalidPayoutSchedule");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "InvalidPayoutSchedule");
+    }
+  });
+});
+
+describe("devquest-vault cliff payouts", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const payee = Keypair.generate();
+  const { vaultState, vault } = deriveVaultAccounts(
+    program.programId,
+    admin.publicKey
+  );
+  const amount = 0.1 * anchor.web3.LAMPORTS_PER_SOL;
+  const interval = 2;
+  let createdAt: number;
+  let cliffTime: number;
+
+  before(async () => {
+    await createFundedVault({
+      admin,
+      payees: [payee],
+      deposit: 2 * anchor.web3.LAMPORTS_PER_SOL,
+    });
+    await program.methods
+      .setMinInterval(new anchor.BN(interval))
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+
+    createdAt = Math.floor(Date.now() / 1000);
+    cliffTime = createdAt + 6;
+    await program.methods
+      .scheduleCliffPayout(
+        payee.publicKey,
+        new anchor.BN(amount),
+        new anchor.BN(cliffTime),
+        new anchor.BN(interval)
+      )
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+  });
+
+  // Test: Nothing can be claimed before the cliff, even once periods have accrued
+  it("Cannot claim before the cliff", async () => {
+    await sleep(interval + 1);
+    const vaultBefore = await provider.connection.getBalance(vault);
+    try {
+      await program.methods
+        .claimPayout()
+        .accountsPartial({ user: payee.publicKey, vaultState, vault })
+        .signers([payee])
+        .rpc();
+
+      assert.fail("Expected error when claiming before the cliff");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "CliffNotReached");
+    }
+    assert.equal(await provider.connection.getBalance(vault), vaultBefore);
+  });
+
+  // Test: At the cliff every period accrued since creation is released at once
+  it("Claims the accrued lump sum at the cliff", async () => {
+    await sleep(cliffTime - Math.floor(Date.now() / 1000));
+    const payeeBefore = await provider.connection.getBalance(payee.publicKey);
+    await program.methods
+      .claimPayout()
+      .accountsPartial({ user: payee.publicKey, vaultState, vault })
+      .signers([payee])
+      .rpc();
+    const received =
+      (await provider.connection.getBalance(payee.publicKey)) - payeeBefore;
+
+    // (cliff - creation) / interval periods have accrued by the cliff
+    const accrued = Math.floor((cliffTime - createdAt) / interval);
+    assert.equal(received % amount, 0);
+    assert.isAtLeast(received, accrued * amount);
+  });
+});
+
+// Test suite: initializing for another admin and handing the admin role over
+describe("devquest-vault admin handoff", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const nextAdmin = Keypair.generate();
+  const payee = Keypair.generate();
+  const { vaultState, vault } = deriveVaultAccounts(
+    program.programId,
+    admin.publicKey
+  );
+
+  before(async () => {
+    await airdrop(provider, admin.publicKey, 1);
+    await airdrop(provider, nextAdmin.publicKey, 1);
+  });
+
+  // Test: Nobody can occupy another admin's vault address without their signature
+  it("Rejects initializing without the admin's signature", async () => {
+    try {
+      await program.methods
+        .initializeWithAdmin(NO_NAME, new anchor.BN(0))
+        .accountsPartial({
+          user: provider.wallet.publicKey,
+          admin: admin.publicKey,
+          vaultState,
+          vault,
+        })
+        .rpc();
+
+      assert.fail("Expected error when the admin does not sign");
+    } catch (error) {
+      assert.include(String(error), "Missing signature");
+    }
+  });
+
+  // Test: The provider wallet pays for a vault administered by someone else
+  it("Initializes a vault for another admin", async () => {
+    await program.methods
+      .initializeWithAdmin(NO_NAME, new anchor.BN(0))
+      .accountsPartial({
+        user: provider.wallet.publicKey,
+        admin: admin.publicKey,
+        vaultState,
+        vault,
+      })
+      .signers([admin])
+      .rpc();
+
+    const state = await program.account.vaultState.fetch(vaultState);
+    assert.ok(state.admin.equals(admin.publicKey));
+  });
+
+  // Test: Proposing the zero pubkey or the current admin is rejected
+  it("Rejects zero and self proposals", async () => {
+    for (const proposed of [anchor.web3.PublicKey.default, admin.publicKey]) {
+      try {
+        await program.methods
+          .proposeAdmin(proposed)
+          .accountsPartial({ user: admin.publicKey, vaultState })
+          .signers([admin])
+          .rpc();
+
+        assert.fail(`Expected error when proposing ${proposed.toBase58()}`);
+      } catch (error) {
+        assert.equal(error.error.errorCode.code, "InvalidAdmin");
+      }
+    }
+  });
+
+  // Test: The proposed admin takes over once they accept
+  it("Hands the admin role over", async () => {
+    await program.methods
+      .proposeAdmin(nextAdmin.publicKey)
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+    await program.methods
+      .acceptAdmin()
+      .accountsPartial({ user: nextAdmin.publicKey, vaultState })
+      .signers([nextAdmin])
+      .rpc();
+
+    const state = await program.account.vaultState.fetch(vaultState);
+    assert.ok(state.admin.equals(nextAdmin.publicKey));
+    assert.isNull(state.pendingAdmin);
+
+    // The state PDA is still derived from the original admin, so the new
+    // admin can keep using admin-only instructions
+    await program.methods
+      .addPayee(payee.publicKey, NO_NAME)
+      .accountsPartial({ user: nextAdmin.publicKey, vaultState })
+      .signers([nextAdmin])
+      .rpc();
+  });
+
+  // Test: The vault cannot be closed under a pending handoff until it is cancelled
+  it("Rejects closing during a pending handoff", async () => {
+    await program.methods
+      .proposeAdmin(admin.publicKey)
+      .accountsPartial({ user: nextAdmin.publicKey, vaultState })
+      .signers([nextAdmin])
+      .rpc();
+    try {
+      await program.methods
+        .close()
+        .accountsPartial({ user: nextAdmin.publicKey, vaultState, vault })
+        .signers([nextAdmin])
+        .rpc();
+      assert.fail("Should have failed with HandoffInProgress");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "HandoffInProgress");
+    }
+
+    await program.methods
+      .cancelAdminProposal()
+      .accountsPartial({ user: nextAdmin.publicKey, vaultState })
+      .signers([nextAdmin])
+      .rpc();
+    const state = await program.account.vaultState.fetch(vaultState);
+    assert.isNull(state.pendingAdmin);
+
+    await program.methods
+      .close()
+      .accountsPartial({ user: nextAdmin.publicKey, vaultState, vault })
+      .signers([nextAdmin])
+      .rpc();
+    assert.isNull(await provider.connection.getAccountInfo(vaultState));
+  });
+});
+
+// Test suite: capping the vault balance
+describe("devquest-vault deposit cap", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const { vaultState, vault } = deriveVaultAccounts(
+    program.programId,
+    admin.publicKey
+  );
+  const cap = 1 * anchor.web3.LAMPORTS_PER_SOL;
+
+  before(async () => {
+    await createFundedVault({ admin });
+    await program.methods
+      .setMaxBalance(new anchor.BN(cap))
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+  });
+
+  // Test: A deposit pushing the usable balance over the cap fails
+  it("Rejects a deposit over the cap", async () => {
+    try {
+      await program.methods
+        .deposit(new anchor.BN(1.5 * anchor.web3.LAMPORTS_PER_SOL))
+        .accountsPartial({ user: admin.publicKey, vaultState, vault })
+        .signers([admin])
+        .rpc();
+
+      assert.fail("Expected error when depositing over the cap");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "DepositCapExceeded");
+    }
+  });
+
+  // Test: A vault holding exactly its rent floor has nothing usable, so one lamport over the cap fails
+  it("Rejects one lamport over the cap at the rent boundary", async () => {
+    const rentExempt =
+      await provider.connection.getMinimumBalanceForRentExemption(0);
+    assert.equal(await provider.connection.getBalance(vault), rentExempt);
+    try {
+      await program.methods
+        .deposit(new anchor.BN(cap + 1))
+        .accountsPartial({ user: admin.publicKey, vaultState, vault })
+        .signers([admin])
+        .rpc();
+
+      assert.fail("Expected error when depositing over the cap");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "DepositCapExceeded");
+    }
+  });
+
+  // Test: Deposits up to the cap succeed; rent-exempt lamports do not count
+  it("Accepts deposits up to the cap", async () => {
+    await program.methods
+      .deposit(new anchor.BN(cap))
+      .accountsPartial({ user: admin.publicKey, vaultState, vault })
+      .signers([admin])
+      .rpc();
+
+    const rentExempt =
+      await provider.connection.getMinimumBalanceForRentExemption(0);
+    assert.equal(await provider.connection.getBalance(vault), rentExempt + cap);
+  });
+
+  // Test: At the cap, even a single lamport or an overflowing amount is rejected
+  it("Rejects deposits once the cap is reached", async () => {
+    for (const amount of [new anchor.BN(1), new anchor.BN("18446744073709551615")]) {
+      try {
+        await program.methods
+          .deposit(amount)
+          .accountsPartial({ user: admin.publicKey, vaultState, vault })
+          .signers([admin])
+          .rpc();
+
+        assert.fail("Expected error when depositing over the cap");
+      } catch (error) {
+        assert.equal(error.error.errorCode.code, "DepositCapExceeded");
+      }
+    }
+  });
+
+  // Test: Wrapping SOL does not get around the cap, and wrapped SOL counts towards it
+  it("Counts wrapped deposits and holdings towards the cap", async () => {
+    const nativeMint = new anchor.web3.PublicKey(
+      "So11111111111111111111111111111111111111112"
+    );
+    const vaultWsol = anchor.utils.token.associatedAddress({ mint: nativeMint, owner: vault });
+    const wrap = (amount: number) =>
+      program.methods
+        .depositAndWrap(new anchor.BN(amount))
+        .accountsPartial({ user: admin.publicKey, vault, vaultState, nativeMint, vaultWsol })
+        .signers([admin])
+        .rpc();
+    const expectCapExceeded = async (amount: number) => {
+      try {
+        await wrap(amount);
+        assert.fail("Expected error when wrapping over the cap");
+      } catch (error) {
+        assert.equal(error.error.errorCode.code, "DepositCapExceeded");
+      }
+    };
+
+    // The native balance already fills the cap
+    await expectCapExceeded(1);
+
+    // With room for half a SOL more, the wrapped half fills it
+    await program.methods
+      .setMaxBalance(new anchor.BN(1.5 * cap))
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+    await wrap(0.5 * cap);
+    await expectCapExceeded(1);
+  });
+});
+
+// Test suite: two-step withdrawals above the instant limit
+describe("devquest-vault delayed withdrawals", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const { vaultState, vault } = deriveVaultAccounts(
+    program.programId,
+    admin.publicKey
+  );
+  const large = new anchor.BN(1 * anchor.web3.LAMPORTS_PER_SOL);
+  const delay = 3;
+
+  before(async () => {
+    await createFundedVault({
+      admin,
+      deposit: 3 * anchor.web3.LAMPORTS_PER_SOL,
+    });
+    await program.methods
+      .setWithdrawalDelay(
+        new anchor.BN(0.5 * anchor.web3.LAMPORTS_PER_SOL),
+        new anchor.BN(delay)
+      )
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+  });
+
+  // Test: Withdrawals up to the instant limit go through immediately
+  it("Withdraws below the instant limit without delay", async () => {
+    await program.methods
+      .withdraw(new anchor.BN(0.1 * anchor.web3.LAMPORTS_PER_SOL))
+      .accountsPartial({ user: admin.publicKey, vaultState, vault })
+      .signers([admin])
+      .rpc();
+  });
+
+  // Test: Larger withdrawals must be requested first
+  it("Rejects a direct withdrawal above the instant limit", async () => {
+    try {
+      await program.methods
+        .withdraw(large)
+        .accountsPartial({ user: admin.publicKey, vaultState, vault })
+        .signers([admin])
+        .rpc();
+
+      assert.fail("Expected error when withdrawing above the instant limit");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "WithdrawalDelayRequired");
+    }
+  });
+
+  // Test: Amounts that need no delay cannot occupy the pending slot
+  it("Rejects requests within the instant limit", async () => {
+    for (const amount of [0, 0.5 * anchor.web3.LAMPORTS_PER_SOL]) {
+      try {
+        await program.methods
+          .requestWithdrawal(new anchor.BN(amount))
+          .accountsPartial({ user: admin.publicKey, vaultState, vault })
+          .signers([admin])
+          .rpc();
+
+        assert.fail(`Expected error when requesting ${amount} lamports`);
+      } catch (error) {
+        assert.equal(error.error.errorCode.code, "WithinInstantLimit");
+      }
+    }
+  });
+
+  // Test: A requested withdrawal executes only once the delay has elapsed
+  it("Executes a requested withdrawal after the delay", async () => {
+    await program.methods
+      .requestWithdrawal(large)
+      .accountsPartial({ user: admin.publicKey, vaultState, vault })
+      .signers([admin])
+      .rpc();
+
+    try {
+      await program.methods
+        .executeWithdrawal()
+        .accountsPartial({ user: admin.publicKey, vaultState, vault })
+        .signers([admin])
+        .rpc();
+
+      assert.fail("Expected error when executing before the delay");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "WithdrawalDelayNotElapsed");
+    }
+
+    await sleep(delay + 1);
+    const vaultBefore = await provider.connection.getBalance(vault);
+    await program.methods
+      .executeWithdrawal()
+      .accountsPartial({ user: admin.publicKey, vaultState, vault })
+      .signers([admin])
+      .rpc();
+    assert.equal(
+      vaultBefore - (await provider.connection.getBalance(vault)),
+      large.toNumber()
+    );
+
+    const state = await program.account.vaultState.fetch(vaultState);
+    assert.isNull(state.pendingWithdrawal);
+  });
+
+  // Test: The admin can abort a suspicious pending request
+  it("Cancels a pending withdrawal", async () => {
+    await program.methods
+      .requestWithdrawal(large)
+      .accountsPartial({ user: admin.publicKey, vaultState, vault })
+      .signers([admin])
+      .rpc();
+    await program.methods
+      .cancelWithdrawal()
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+
+    await sleep(delay + 1);
+    try {
+      await program.methods
+        .executeWithdrawal()
+        .accountsPartial({ user: admin.publicKey, vaultState, vault })
+        .signers([admin])
+        .rpc();
+
+      assert.fail("Expected error when executing a cancelled withdrawal");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "NoPendingWithdrawal");
+    }
+  });
+});
+
+// Test suite: layout versioning and pausing
+describe("devquest-vault versioning and pause", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const { vaultState, vault } = deriveVaultAccounts(
+    program.programId,
+    admin.publicKey
+  );
+  const amount = new anchor.BN(0.1 * anchor.web3.LAMPORTS_PER_SOL);
+
+  before(async () => {
+    await createFundedVault({
+      admin,
+      deposit: 1 * anchor.web3.LAMPORTS_PER_SOL,
+    });
+  });
+
+  // Test: New vaults start at the current layout version and cannot be migrated again
+  it("Rejects migrating a current vault", async () => {
+    const state = await program.account.vaultState.fetch(vaultState);
+    assert.equal(state.version, 2);
+    assert.isFalse(state.isPaused);
+
+    try {
+      await program.methods
+        .migrate()
+        .accountsPartial({ user: admin.publicKey, vaultState })
+        .signers([admin])
+        .rpc();
+
+      assert.fail("Expected error when migrating a current vault");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "AlreadyMigrated");
+    }
+  });
+
+  // Test: Withdrawals are halted while paused and resume afterwards
+  it("Blocks withdrawals while paused", async () => {
+    await program.methods
+      .setPaused(true)
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+    try {
+      await program.methods
+        .withdraw(amount)
+        .accountsPartial({ user: admin.publicKey, vaultState, vault })
+        .signers([admin])
+        .rpc();
+
+      assert.fail("Expected error when withdrawing from a paused vault");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "VaultPaused");
+    }
+
+    await program.methods
+      .setPaused(false)
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+    await program.methods
+      .withdraw(amount)
+      .accountsPartial({ user: admin.publicKey, vaultState, vault })
+      .signers([admin])
+      .rpc();
+  });
+});
+
+// Test suite: wrapping deposits into wSOL for SPL integrations
+describe("devquest-vault wrapped SOL", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const { vaultState, vault } = deriveVaultAccounts(
+    program.programId,
+    admin.publicKey
+  );
+  const nativeMint = new anchor.web3.PublicKey(
+    "So11111111111111111111111111111111111111112"
+  );
+  const vaultWsol = anchor.utils.token.associatedAddress({
+    mint: nativeMint,
+    owner: vault,
+  });
+  const amount = 0.5 * anchor.web3.LAMPORTS_PER_SOL;
+
+  before(async () => {
+    await createFundedVault({ admin });
+  });
+
+  // Test: Deposited SOL lands in the vault's wSOL account as synced tokens
+  it("Deposits and wraps SOL", async () => {
+    await program.methods
+      .depositAndWrap(new anchor.BN(amount))
+      .accountsPartial({
+        user: admin.publicKey,
+        vault,
+        vaultState,
+        nativeMint,
+        vaultWsol,
+      })
+      .signers([admin])
+      .rpc();
+
+    const balance = await provider.connection.getTokenAccountBalance(
+      vaultWsol
+    );
+    assert.equal(balance.value.amount, amount.toString());
+    const state = await program.account.vaultState.fetch(vaultState);
+    assert.isTrue(state.wsolEnabled);
+  });
+
+  // Test: Unwrapping returns the wrapped SOL and the account rent to the vault
+  it("Unwraps wSOL back into the vault", async () => {
+    const wsolLamports = await provider.connection.getBalance(vaultWsol);
+    const vaultBefore = await provider.connection.getBalance(vault);
+
+    await program.methods
+      .unwrapWsol()
+      .accountsPartial({
+        user: admin.publicKey,
+        vault,
+        vaultState,
+        nativeMint,
+        vaultWsol,
+      })
+      .signers([admin])
+      .rpc();
+
+    assert.equal(
+      await provider.connection.getBalance(vault),
+      vaultBefore + wsolLamports
+    );
+    assert.isNull(await provider.connection.getAccountInfo(vaultWsol));
+    const state = await program.account.vaultState.fetch(vaultState);
+    assert.isFalse(state.wsolEnabled);
+  });
+});
+
+
+// Test suite: moving funds between vaults with the same admin
+describe("devquest-vault transfer between vaults", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const secondCreator = Keypair.generate();
+  const stranger = Keypair.generate();
+  const source = deriveVaultAccounts(program.programId, admin.publicKey);
+  const destination = deriveVaultAccounts(program.programId, secondCreator.publicKey);
+  const foreign = deriveVaultAccounts(program.programId, stranger.publicKey);
+  const SOL = anchor.web3.LAMPORTS_PER_SOL;
+
+  before(async () => {
+    await createFundedVault({ admin, deposit: 1 * SOL });
+    await createFundedVault({ admin: secondCreator });
+    await createFundedVault({ admin: stranger });
+
+    // Hand the second vault over so the admin runs both
+    await program.methods
+      .proposeAdmin(admin.publicKey)
+      .accountsPartial({ user: secondCreator.publicKey, vaultState: destination.vaultState })
+      .signers([secondCreator])
+      .rpc();
+    await program.methods
+      .acceptAdmin()
+      .accountsPartial({ user: admin.publicKey, vaultState: destination.vaultState })
+      .signers([admin])
+      .rpc();
+  });
+
+  const transferTo = (
+    target: { vaultState: anchor.web3.PublicKey; vault: anchor.web3.PublicKey },
+    amount: number
+  ) =>
+    program.methods
+      .transferBetweenVaults(new anchor.BN(amount))
+      .accountsPartial({
+        user: admin.publicKey,
+        sourceVault: source.vault,
+        sourceState: source.vaultState,
+        destinationVault: target.vault,
+        destinationState: target.vaultState,
+      })
+      .signers([admin])
+      .rpc({ commitment: "confirmed" });
+
+  // Test: Funds move directly from one vault PDA to the other
+  it("Moves funds to another vault with the same admin", async () => {
+    const before = await provider.connection.getBalance(destination.vault);
+    const tx = await transferTo(destination, 0.4 * SOL);
+
+    const after = await provider.connection.getBalance(destination.vault);
+    assert.equal(after - before, 0.4 * SOL);
+    const event = findEvent(await fetchEvents(program, tx), "VaultTransferEvent");
+    assert.ok(event, "VaultTransferEvent should be emitted");
+    assert.ok(event.data.sourceVaultState.equals(source.vaultState));
+    assert.ok(event.data.destinationVaultState.equals(destination.vaultState));
+    assert.equal(event.data.amount.toNumber(), 0.4 * SOL);
+  });
+
+  // Test: The admin must also run the destination vault
+  it("Rejects a destination vault with another admin", async () => {
+    try {
+      await transferTo(foreign, 0.1 * SOL);
+      assert.fail("Should have failed with UnauthorizedAdmin");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "UnauthorizedAdmin");
+    }
+  });
+
+  // Test: The source keeps its rent floor
+  it("Rejects moving more than the source can spare", async () => {
+    try {
+      await transferTo(destination, 0.6 * SOL + 1);
+      assert.fail("Should have failed with InsufficientFunds");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "InsufficientFunds");
+    }
+  });
+});
+
+// Test suite: successive vaults of the same admin
+describe("devquest-vault vault nonces", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const first = deriveVaultAccounts(program.programId, admin.publicKey);
+  const second = deriveVaultAccounts(program.programId, admin.publicKey, 1);
+
+  before(async () => {
+    await airdrop(provider, admin.publicKey, 2);
+  });
+
+  const initialize = (
+    accounts: { vaultState: anchor.web3.PublicKey; vault: anchor.web3.PublicKey },
+    nonce: number
+  ) =>
+    program.methods
+      .initialize(NO_NAME, new anchor.BN(nonce))
+      .accountsPartial({ user: admin.publicKey, ...accounts })
+      .signers([admin])
+      .rpc();
+
+  // Test: A closed vault is replaced by a new vault at a different address
+  it("Creates a second vault at a distinct address after closing the first", async () => {
+    await initialize(first, 0);
+    await program.methods
+      .close()
+      .accountsPartial({ user: admin.publicKey, ...first })
+      .signers([admin])
+      .rpc();
+
+    await initialize(second, 1);
+    assert.isFalse(second.vaultState.equals(first.vaultState));
+    const state = await program.account.vaultState.fetch(second.vaultState);
+    assert.equal(state.vaultNonce.toNumber(), 1);
+    assert.ok(state.admin.equals(admin.publicKey));
+
+    // The new vault works like any other
+    await program.methods
+      .deposit(new anchor.BN(0.1 * anchor.web3.LAMPORTS_PER_SOL))
+      .accountsPartial({ user: admin.publicKey, ...second })
+      .signers([admin])
+      .rpc();
+    const info = await program.methods
+      .getVaultInfo()
+      .accountsPartial(second)
+      .view();
+    assert.equal(info.usableBalance.toNumber(), 0.1 * anchor.web3.LAMPORTS_PER_SOL);
+  });
+
+  // Test: A closed vault's nonce cannot be used to re-create a vault at its address
+  it("Rejects re-initializing a closed vault with the same nonce", async () => {
+    try {
+      await initialize(first, 0);
+      assert.fail("Should have failed with StaleVaultNonce");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "StaleVaultNonce");
+    }
+    assert.isNull(await provider.connection.getAccountInfo(first.vaultState));
+
+    const [counter] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("counter"), admin.publicKey.toBuffer()],
+      program.programId
+    );
+    const record = await program.account.vaultCounter.fetch(counter);
+    assert.equal(record.nextNonce.toNumber(), 2);
+  });
+
+  // Test: The state account must match the nonce it is initialized with
+  it("Rejects a state account derived from another nonce", async () => {
+    try {
+      await initialize(deriveVaultAccounts(program.programId, admin.publicKey, 3), 2);
+      assert.fail("Should have failed with ConstraintSeeds");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "ConstraintSeeds");
+    }
+  });
+});
+
+// Test suite: closing a vault into a beneficiary account
+describe("devquest-vault close to beneficiary", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const beneficiary = Keypair.generate();
+  const { vaultState, vault } = deriveVaultAccounts(
+    program.programId,
+    admin.publicKey
+  );
+
+  before(async () => {
+    await createFundedVault({
+      admin,
+      deposit: 1 * anchor.web3.LAMPORTS_PER_SOL,
+    });
+  });
+
+  // Test: The beneficiary account must match the argument
+  it("Rejects a mismatched beneficiary account", async () => {
+    try {
+      await program.methods
+        .closeTo(beneficiary.publicKey)
+        .accountsPartial({
+          user: admin.publicKey,
+          beneficiaryAccount: Keypair.generate().publicKey,
+          vaultState,
+          vault,
+        })
+        .signers([admin])
+        .rpc();
+      assert.fail("Should have failed with InvalidBeneficiary");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "InvalidBeneficiary");
+    }
+  });
+
+  // Test: Vault funds go to the beneficiary, state rent back to the admin
+  it("Sends the vault's funds to the beneficiary", async () => {
+    const vaultLamports = await provider.connection.getBalance(vault);
+    const stateLamports = await provider.connection.getBalance(vaultState);
+    const adminBefore = await provider.connection.getBalance(admin.publicKey);
+
+    const tx = await program.methods
+      .closeTo(beneficiary.publicKey)
+      .accountsPartial({
+        user: admin.publicKey,
+        beneficiaryAccount: beneficiary.publicKey,
+        vaultState,
+        vault,
+      })
+      .signers([admin])
+      .rpc({ commitment: "confirmed" });
+
+    assert.equal(
+      await provider.connection.getBalance(beneficiary.publicKey),
+      vaultLamports
+    );
+    assert.equal(await provider.connection.getBalance(vault), 0);
+    assert.isNull(await provider.connection.getAccountInfo(vaultState));
+    const adminAfter = await provider.connection.getBalance(admin.publicKey);
+    assert.approximately(adminAfter - adminBefore, stateLamports, 10_000);
+
+    const closed = findEvent(await fetchEvents(program, tx), "VaultClosedEvent");
+    assert.ok(closed, "VaultClosedEvent should be emitted");
+    assert.ok(closed.data.beneficiary.equals(beneficiary.publicKey));
+    assert.equal(closed.data.amount.toNumber(), vaultLamports);
+  });
+});
+
+// Test suite: accounts from different vaults cannot be mixed
+describe("devquest-vault mismatched accounts", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const adminA = Keypair.generate();
+  const adminB = Keypair.generate();
+  const a = deriveVaultAccounts(program.programId, adminA.publicKey);
+  const b = deriveVaultAccounts(program.programId, adminB.publicKey);
+
+  before(async () => {
+    for (const admin of [adminA, adminB]) {
+      await createFundedVault({ admin, deposit: 1 * anchor.web3.LAMPORTS_PER_SOL });
+    }
+  });
+
+  // Test: A vault that was not derived from the given state is rejected
+  it("Rejects another vault's funds account on withdraw", async () => {
+    try {
+      await program.methods
+        .withdraw(new anchor.BN(0.1 * anchor.web3.LAMPORTS_PER_SOL))
+        .accountsPartial({
+          user: adminA.publicKey,
+          vaultState: a.vaultState,
+          vault: b.vault,
+        })
+        .signers([adminA])
+        .rpc();
+      assert.fail("Should have failed with ConstraintSeeds");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "ConstraintSeeds");
+    }
+  });
+
+  // Test: Deposits cannot be credited against a different vault's state
+  it("Rejects another vault's state on deposit", async () => {
+    try {
+      await program.methods
+        .deposit(new anchor.BN(0.1 * anchor.web3.LAMPORTS_PER_SOL))
+        .accountsPartial({
+          user: adminA.publicKey,
+          vaultState: b.vaultState,
+          vault: a.vault,
+        })
+        .signers([adminA])
+        .rpc();
+      assert.fail("Should have failed with ConstraintSeeds");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "ConstraintSeeds");
+    }
+  });
+
+  // Test: A matching state and vault pair still only answers to its own admin
+  it("Rejects closing another admin's vault", async () => {
+    try {
+      await program.methods
+        .close()
+        .accountsPartial({
+          user: adminA.publicKey,
+          vaultState: b.vaultState,
+          vault: b.vault,
+        })
+        .signers([adminA])
+        .rpc();
+      assert.fail("Should have failed with UnauthorizedAdmin");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "UnauthorizedAdmin");
+    }
+  });
+
+  // Test: An account the program does not own cannot stand in for the state
+  it("Rejects a state account not owned by the program", async () => {
+    try {
+      await program.methods
+        .withdraw(new anchor.BN(0.1 * anchor.web3.LAMPORTS_PER_SOL))
+        .accountsPartial({
+          user: adminA.publicKey,
+          vaultState: adminA.publicKey,
+          vault: a.vault,
+        })
+        .signers([adminA])
+        .rpc();
+      assert.fail("Should have failed with AccountOwnedByWrongProgram");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "AccountOwnedByWrongProgram");
+    }
+  });
+});
+
+// Test suite: funding a vault and adding payees and schedules in one transaction
+describe("devquest-vault setup", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const { vault, vaultState } = deriveVaultAccounts(program.programId, admin.publicKey);
+  const payeeA = Keypair.generate().publicKey;
+  const payeeB = Keypair.generate().publicKey;
+  const deposit = 1 * anchor.web3.LAMPORTS_PER_SOL;
+
+  before(async () => {
+    await createFundedVault({ admin });
+  });
+
+  // Test: A failing step rolls back the deposit, payees and schedules
+  it("Applies nothing when a payee is duplicated", async () => {
+    const vaultBefore = await provider.connection.getBalance(vault);
+    const start = new anchor.BN(Math.floor(Date.now() / 1000) + 60);
+    try {
+      await program.methods
+        .setup(new anchor.BN(deposit), [payeeA, payeeA], [
+          { payee: payeeA, amount: new anchor.BN(1000), startTime: start, interval: new anchor.BN(3600) },
+        ])
+        .accountsPartial({ user: admin.publicKey, vault, vaultState })
+        .signers([admin])
+        .rpc();
+      assert.fail("Should have failed with PayeeAlreadyExists");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "PayeeAlreadyExists");
+    }
+
+    const state = await program.account.vaultState.fetch(vaultState);
+    assert.equal(state.payees.length, 0);
+    assert.equal(state.payoutSchedules.length, 0);
+    assert.equal(await provider.connection.getBalance(vault), vaultBefore);
+  });
+
+  // Test: Deposit, payees and schedules all land in a single transaction
+  it("Sets up a vault in one transaction", async () => {
+    const vaultBefore = await provider.connection.getBalance(vault);
+    const start = new anchor.BN(Math.floor(Date.now() / 1000) + 60);
+    await program.methods
+      .setup(new anchor.BN(deposit), [payeeA, payeeB], [
+        { payee: payeeA, amount: new anchor.BN(1000), startTime: start, interval: new anchor.BN(3600) },
+        { payee: payeeB, amount: new anchor.BN(2000), startTime: start, interval: new anchor.BN(7200) },
+      ])
+      .accountsPartial({ user: admin.publicKey, vault, vaultState })
+      .signers([admin])
+      .rpc();
+
+    const state = await program.account.vaultState.fetch(vaultState);
+    assert.equal(state.payees.length, 2);
+    assert.ok(state.payees[0].equals(payeeA));
+    assert.ok(state.payees[1].equals(payeeB));
+    assert.equal(state.payoutSchedules.length, 2);
+    assert.ok(state.payoutSchedules[1].payee.equals(payeeB));
+    assert.equal(state.payoutSchedules[1].kind.fixed[0].toNumber(), 2000);
+    assert.equal(state.payoutSchedules[1].interval.toNumber(), 7200);
+    assert.equal(await provider.connection.getBalance(vault), vaultBefore + deposit);
+  });
+
+  // Test: Schedules are validated just like schedule_payout
+  it("Rejects a schedule for an unknown payee", async () => {
+    const start = new anchor.BN(Math.floor(Date.now() / 1000) + 60);
+    try {
+      await program.methods
+        .setup(new anchor.BN(0), [], [
+          { payee: Keypair.generate().publicKey, amount: new anchor.BN(1000), startTime: start, interval: new anchor.BN(3600) },
+        ])
+        .accountsPartial({ user: admin.publicKey, vault, vaultState })
+        .signers([admin])
+        .rpc();
+      assert.fail("Should have failed with PayeeNotFound");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "PayeeNotFound");
+    }
+  });
+
+  // Test: A payee already holding an active schedule cannot be given a second one
+  it("Rejects a second schedule for the same payee", async () => {
+    const start = new anchor.BN(Math.floor(Date.now() / 1000) + 60);
+    const scheduleFor = (payee: anchor.web3.PublicKey) =>
+      ({ payee, amount: new anchor.BN(1000), startTime: start, interval: new anchor.BN(3600) });
+    const expectRejected = async (payees: anchor.web3.PublicKey[], schedules) => {
+      try {
+        await program.methods
+          .setup(new anchor.BN(0), payees, schedules)
+          .accountsPartial({ user: admin.publicKey, vault, vaultState })
+          .signers([admin])
+          .rpc();
+        assert.fail("Should have failed with InvalidPayoutSchedule");
+      } catch (error) {
+        assert.equal(error.error.errorCode.code, "InvalidPayoutSchedule");
+      }
+    };
+
+    // payeeA already holds a schedule from the previous test
+    await expectRejected([], [scheduleFor(payeeA)]);
+    // A new payee cannot be scheduled twice in the same call either
+    const payeeC = Keypair.generate().publicKey;
+    await expectRejected([payeeC], [scheduleFor(payeeC), scheduleFor(payeeC)]);
+
+    const state = await program.account.vaultState.fetch(vaultState);
+    assert.equal(state.payoutSchedules.length, 2);
+  });
+});
+
+// Test suite: schedules paying SOL and a token together
+describe("devquest-vault dual-asset payouts", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const payee = Keypair.generate();
+  const { vaultState, vault } = deriveVaultAccounts(program.programId, admin.publicKey);
+  // wSOL stands in for the governance token, so no extra mint has to be set up
+  const nativeMint = new anchor.web3.PublicKey(
+    "So11111111111111111111111111111111111111112"
+  );
+  const vaultTokenAccount = anchor.utils.token.associatedAddress({ mint: nativeMint, owner: vault });
+  const payeeTokenAccount = anchor.utils.token.associatedAddress({
+    mint: nativeMint,
+    owner: payee.publicKey,
+  });
+  const lamportsPerPeriod = 1000;
+  const tokensPerPeriod = 5000;
+  const interval = 2;
+
+  before(async () => {
+    await createFundedVault({
+      admin,
+      payees: [payee],
+      deposit: 1 * anchor.web3.LAMPORTS_PER_SOL,
+    });
+    // Fund the vault's token account
+    await program.methods
+      .depositAndWrap(new anchor.BN(0.5 * anchor.web3.LAMPORTS_PER_SOL))
+      .accountsPartial({ user: admin.publicKey, vault, vaultState, nativeMint, vaultWsol: vaultTokenAccount })
+      .signers([admin])
+      .rpc();
+    // Create the payee's token account through the associated token program
+    const createAta = new anchor.web3.TransactionInstruction({
+      programId: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
+      keys: [
+        { pubkey: payee.publicKey, isSigner: true, isWritable: true },
+        { pubkey: payeeTokenAccount, isSigner: false, isWritable: true },
+        { pubkey: payee.publicKey, isSigner: false, isWritable: false },
+        { pubkey: nativeMint, isSigner: false, isWritable: false },
+        { pubkey: anchor.web3.SystemProgram.programId, isSigner: false, isWritable: false },
+        { pubkey: anchor.utils.token.TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+      ],
+      data: Buffer.alloc(0),
+    });
+    await anchor.web3.sendAndConfirmTransaction(
+      provider.connection,
+      new anchor.web3.Transaction().add(createAta),
+      [payee]
+    );
+
+    await program.methods
+      .setMinInterval(new anchor.BN(interval))
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+    await program.methods
+      .schedulePayout(
+        payee.publicKey,
+        new anchor.BN(lamportsPerPeriod),
+        new anchor.BN(Math.floor(Date.now() / 1000) + 2),
+        new anchor.BN(interval)
+      )
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+    await program.methods
+      .setPayoutToken(payee.publicKey, nativeMint, new anchor.BN(tokensPerPeriod))
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+  });
+
+  // Test: The schedule records the token leg
+  it("Stores the token mint and amount on the schedule", async () => {
+    const state = await program.account.vaultState.fetch(vaultState);
+    assert.ok(state.payoutSchedules[0].tokenMint.equals(nativeMint));
+    assert.equal(state.payoutSchedules[0].tokenAmount.toNumber(), tokensPerPeriod);
+  });
+
+  // Test: A dual-asset claim cannot skip the token leg
+  it("Rejects a dual-asset claim without token accounts", async () => {
+    await sleep(3);
+    try {
+      await program.methods
+        .claimPayout()
+        .accountsPartial({ user: payee.publicKey, vaultState, vault })
+        .signers([payee])
+        .rpc();
+      assert.fail("Should have failed with InvalidTokenAccount");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "InvalidTokenAccount");
+    }
+  });
+
+  // Test: One claim pays the lamports and the tokens for the same periods
+  it("Claims SOL and tokens in one transaction", async () => {
+    const tx = await program.methods
+      .claimPayout()
+      .accountsPartial({
+        user: payee.publicKey,
+        vaultState,
+        vault,
+        tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+        vaultTokenAccount,
+        payeeTokenAccount,
+      })
+      .signers([payee])
+      .rpc({ commitment: "confirmed" });
+
+    const claimed = findEvent(await fetchEvents(program, tx), "PayoutClaimedEvent");
+    assert.ok(claimed, "PayoutClaimedEvent should be emitted");
+    const periods = claimed.data.amount.toNumber() / lamportsPerPeriod;
+    assert.isAtLeast(periods, 1);
+    assert.equal(claimed.data.tokenAmount.toNumber(), periods * tokensPerPeriod);
+    const balance = await provider.connection.getTokenAccountBalance(payeeTokenAccount);
+    assert.equal(balance.value.amount, (periods * tokensPerPeriod).toString());
+  });
+
+  // Test: The token leg is charged against the payee's limit for its mint
+  it("Holds the token leg to the payee's limit for its mint", async () => {
+    const setTokenLimit = (limit: number) =>
+      program.methods
+        .setEpochLimit(payee.publicKey, nativeMint, new anchor.BN(limit), new anchor.BN(3600))
+        .accountsPartial({ user: admin.publicKey, vaultState })
+        .signers([admin])
+        .rpc();
+    const claim = () =>
+      program.methods
+        .claimPayout()
+        .accountsPartial({
+          user: payee.publicKey,
+          vaultState,
+          vault,
+          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+          vaultTokenAccount,
+          payeeTokenAccount,
+        })
+        .signers([payee])
+        .rpc({ commitment: "confirmed" });
+    const tokenLimit = async () =>
+      (await program.account.vaultState.fetch(vaultState)).epochLimits.find(
+        (l) => l.mint && l.mint.equals(nativeMint)
+      ).spending;
+
+    await setTokenLimit(100 * tokensPerPeriod);
+    await sleep(interval + 1);
+    const claimed = findEvent(await fetchEvents(program, await claim()), "PayoutClaimedEvent");
+    assert.equal(
+      (await tokenLimit()).spentAmount.toNumber(),
+      claimed.data.tokenAmount.toNumber()
+    );
+
+    // Once less than a period's tokens is left, the whole claim is rejected
+    await setTokenLimit(tokensPerPeriod - 1);
+    await sleep(interval + 1);
+    const nextPayoutTime = (await program.account.vaultState.fetch(vaultState))
+      .payoutSchedules[0].nextPayoutTime.toNumber();
+    try {
+      await claim();
+      assert.fail("Should have failed with EpochSpendingLimitReached");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "EpochSpendingLimitReached");
+    }
+    const state = await program.account.vaultState.fetch(vaultState);
+    assert.equal(state.payoutSchedules[0].nextPayoutTime.toNumber(), nextPayoutTime);
+    assert.equal((await tokenLimit()).spentAmount.toNumber(), 0);
+  });
+
+  // Test: The SOL and token limits are charged separately and each one rejects on its own
+  it("Enforces limits for different mints independently", async () => {
+    const setLimit = (mint: anchor.web3.PublicKey | null, limit: number) =>
+      program.methods
+        .setEpochLimit(payee.publicKey, mint, new anchor.BN(limit), new anchor.BN(3600))
+        .accountsPartial({ user: admin.publicKey, vaultState })
+        .signers([admin])
+        .rpc();
+    const claim = () =>
+      program.methods
+        .claimPayout()
+        .accountsPartial({
+          user: payee.publicKey,
+          vaultState,
+          vault,
+          tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+          vaultTokenAccount,
+          payeeTokenAccount,
+        })
+        .signers([payee])
+        .rpc({ commitment: "confirmed" });
+    const spent = async (mint: anchor.web3.PublicKey | null) =>
+      (await program.account.vaultState.fetch(vaultState)).epochLimits
+        .find((l) => (mint ? l.mint && l.mint.equals(mint) : l.mint === null))
+        .spending.spentAmount.toNumber();
+    const expectLimitReached = async () => {
+      try {
+        await claim();
+        assert.fail("Should have failed with EpochSpendingLimitReached");
+      } catch (error) {
+        assert.equal(error.error.errorCode.code, "EpochSpendingLimitReached");
+      }
+    };
+    const generousSol = 0.1 * anchor.web3.LAMPORTS_PER_SOL;
+    const generousTokens = 100 * tokensPerPeriod;
+
+    // A used-up SOL limit rejects the claim while the token limit has room
+    await setLimit(null, lamportsPerPeriod - 1);
+    await setLimit(nativeMint, generousTokens);
+    await sleep(interval + 1);
+    await expectLimitReached();
+
+    // A used-up token limit rejects the claim while the SOL limit has room, and SOL
+    // withdrawals only charge the SOL limit
+    await setLimit(null, generousSol);
+    await setLimit(nativeMint, tokensPerPeriod - 1);
+    await expectLimitReached();
+    await program.methods
+      .withdraw(new anchor.BN(lamportsPerPeriod))
+      .accountsPartial({ user: payee.publicKey, vaultState, vault })
+      .signers([payee])
+      .rpc();
+    assert.equal(await spent(null), lamportsPerPeriod);
+    assert.equal(await spent(nativeMint), 0);
+
+    // With room in both, a claim charges each limit in its own units
+    await setLimit(nativeMint, generousTokens);
+    const claimed = findEvent(await fetchEvents(program, await claim()), "PayoutClaimedEvent");
+    assert.equal(await spent(null), lamportsPerPeriod + claimed.data.amount.toNumber());
+    assert.equal(await spent(nativeMint), claimed.data.tokenAmount.toNumber());
+  });
+});
+
+// Test suite: listing only the active schedules
+describe("devquest-vault active schedule listing", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const { vaultState, vault } = deriveVaultAccounts(program.programId, admin.publicKey);
+  const payees = [Keypair.generate(), Keypair.generate(), Keypair.generate()].map((k) => k.publicKey);
+  const startTime = Math.floor(Date.now() / 1000) + 600;
+
+  const listActive = () =>
+    program.methods.listActiveSchedules().accountsPartial({ vaultState, vault }).view();
+
+  before(async () => {
+    await createFundedVault({ admin });
+  });
+
+  // Test: A vault without schedules lists nothing
+  it("Returns an empty list without schedules", async () => {
+    assert.deepEqual(await listActive(), []);
+  });
+
+  // Test: Cancelled schedules are left out of the listing
+  it("Lists only the active schedules", async () => {
+    for (const [i, payee] of payees.entries()) {
+      await program.methods
+        .addPayee(payee, NO_NAME)
+        .accountsPartial({ user: admin.publicKey, vaultState })
+        .signers([admin])
+        .rpc();
+      await program.methods
+        .schedulePayout(payee, new anchor.BN(1000 * (i + 1)), new anchor.BN(startTime), new anchor.BN(3600))
+        .accountsPartial({ user: admin.publicKey, vaultState })
+        .signers([admin])
+        .rpc();
+    }
+    await program.methods
+      .cancelPayout(payees[1])
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+
+    const active = await listActive();
+    assert.equal(active.length, 2);
+    assert.ok(active[0].payee.equals(payees[0]));
+    assert.ok(active[1].payee.equals(payees[2]));
+    assert.equal(active[1].kind.fixed[0].toNumber(), 3000);
+    assert.equal(active[1].nextPayoutTime.toNumber(), startTime);
+    assert.equal(active[1].interval.toNumber(), 3600);
+  });
+});
+
+// Test suite: cancelling a stream part-way through
+describe("devquest-vault stream cancellation", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const payee = Keypair.generate();
+  const { vaultState, vault } = deriveVaultAccounts(program.programId, admin.publicKey);
+  const total = 1 * anchor.web3.LAMPORTS_PER_SOL;
+  const duration = 10;
+  let startTime: number;
+
+  before(async () => {
+    await createFundedVault({
+      admin,
+      payees: [payee],
+      deposit: 2 * anchor.web3.LAMPORTS_PER_SOL,
+    });
+    await program.methods
+      .setMinInterval(new anchor.BN(duration))
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+
+    startTime = Math.floor(Date.now() / 1000) + 2;
+    await program.methods
+      .scheduleStream(
+        payee.publicKey,
+        new anchor.BN(total),
+        new anchor.BN(startTime),
+        new anchor.BN(startTime + duration)
+      )
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+  });
+
+  // Test: Cancelling at about 40% keeps the vested part owed and releases the rest
+  it("Settles the vested part and refunds the rest", async () => {
+    await sleep(startTime + 0.4 * duration - Date.now() / 1000);
+    const tx = await program.methods
+      .cancelPayout(payee.publicKey)
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc({ commitment: "confirmed" });
+
+    const cancelled = findEvent(await fetchEvents(program, tx), "StreamCancelledEvent");
+    assert.ok(cancelled, "StreamCancelledEvent should be emitted");
+    const settled = cancelled.data.settled.toNumber();
+    assert.equal(settled + cancelled.data.refunded.toNumber(), total);
+    // Validator clocks are coarse, so allow some slack around 40%
+    assert.isAtLeast(settled, 0.2 * total);
+    assert.isAtMost(settled, 0.6 * total);
+
+    // The settled balance does not keep the payee from being given a new schedule
+    await program.methods
+      .schedulePayout(
+        payee.publicKey,
+        new anchor.BN(1000),
+        new anchor.BN(Math.floor(Date.now() / 1000) + 3600),
+        new anchor.BN(duration)
+      )
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+
+    // Nothing vests after the cancellation, and the settled amount is claimable at once
+    await sleep(2);
+    const before = await provider.connection.getBalance(payee.publicKey);
+    await program.methods
+      .claimStream()
+      .accountsPartial({ user: payee.publicKey, vaultState, vault })
+      .signers([payee])
+      .rpc();
+    const after = await provider.connection.getBalance(payee.publicKey);
+    assert.approximately(after - before, settled, 10_000);
+
+    const state = await program.account.vaultState.fetch(vaultState);
+    assert.isFalse(state.payoutSchedules[0].isActive);
+    assert.isTrue(state.payoutSchedules[1].isActive);
+  });
+});
+
+// Test suite: point-in-time snapshots for reporting
+describe("devquest-vault snapshots", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const payee = Keypair.generate().publicKey;
+  const { vaultState, vault } = deriveVaultAccounts(program.programId, admin.publicKey);
+
+  const snapshotAddress = (slot: number) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("snapshot"),
+        vaultState.toBuffer(),
+        new anchor.BN(slot).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+
+  const takeSnapshot = async () => {
+    const slot = await provider.connection.getSlot();
+    const snapshot = snapshotAddress(slot);
+    await program.methods
+      .snapshot(new anchor.BN(slot))
+      .accountsPartial({ user: admin.publicKey, vault, vaultState, snapshot })
+      .signers([admin])
+      .rpc();
+    return { slot, snapshot: await program.account.vaultSnapshot.fetch(snapshot) };
+  };
+
+  const deposit = (sol: number) =>
+    program.methods
+      .deposit(new anchor.BN(sol * anchor.web3.LAMPORTS_PER_SOL))
+      .accountsPartial({ user: admin.publicKey, vaultState, vault })
+      .signers([admin])
+      .rpc();
+
+  before(async () => {
+    await createFundedVault({ admin });
+    await program.methods
+      .addPayee(payee, NO_NAME)
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+  });
+
+  // Test: Snapshots at two slots record the balance as it was at each
+  it("Creates snapshots at different slots", async () => {
+    await deposit(1);
+    const first = await takeSnapshot();
+    await deposit(0.5);
+    await sleep(1);
+    const second = await takeSnapshot();
+
+    assert.isAbove(second.slot, first.slot);
+    assert.equal(first.snapshot.slot.toNumber(), first.slot);
+    assert.ok(first.snapshot.vaultState.equals(vaultState));
+    assert.equal(first.snapshot.balance.toNumber(), 1 * anchor.web3.LAMPORTS_PER_SOL);
+    assert.equal(second.snapshot.balance.toNumber(), 1.5 * anchor.web3.LAMPORTS_PER_SOL);
+    assert.equal(second.snapshot.payeeCount, 1);
+    assert.ok(second.snapshot.payeeSpent[0].payee.equals(payee));
+    assert.equal(second.snapshot.payeeSpent[0].spent.toNumber(), 0);
+    assert.isAtLeast(second.snapshot.timestamp.toNumber(), first.snapshot.timestamp.toNumber());
+  });
+
+  // Test: A snapshot cannot be filed under a slot that has not happened yet
+  it("Rejects a future slot", async () => {
+    const slot = (await provider.connection.getSlot()) + 1000;
+    try {
+      await program.methods
+        .snapshot(new anchor.BN(slot))
+        .accountsPartial({ user: admin.publicKey, vault, vaultState, snapshot: snapshotAddress(slot) })
+        .signers([admin])
+        .rpc();
+      assert.fail("Should have failed with InvalidSnapshotSlot");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "InvalidSnapshotSlot");
+    }
+  });
+});
+
+// Test suite: payees starting their own next epoch
+describe("devquest-vault epoch self-reset", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const allowed = Keypair.generate();
+  const denied = Keypair.generate();
+  const { vaultState, vault } = deriveVaultAccounts(program.programId, admin.publicKey);
+  const duration = 3;
+
+  const selfReset = (payee: Keypair) =>
+    program.methods
+      .selfResetEpoch()
+      .accountsPartial({ user: payee.publicKey, vaultState })
+      .signers([payee])
+      .rpc();
+
+  const epochOf = async (payee: Keypair) =>
+    (await program.account.vaultState.fetch(vaultState)).epochLimits.find((e) =>
+      e.payee.equals(payee.publicKey)
+    ).spending;
+
+  before(async () => {
+    await createFundedVault({ admin, payees: [allowed, denied] });
+    for (const payee of [allowed, denied]) {
+      await program.methods
+        .setEpochLimit(payee.publicKey, null, new anchor.BN(1000), new anchor.BN(duration))
+        .accountsPartial({ user: admin.publicKey, vaultState })
+        .signers([admin])
+        .rpc();
+    }
+    await program.methods
+      .setEpochSelfReset(allowed.publicKey, null, true)
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+  });
+
+  // Test: A self-reset before the epoch has ended is rejected
+  it("Rejects a reset before the epoch has elapsed", async () => {
+    try {
+      await selfReset(allowed);
+      assert.fail("Should have failed with EpochNotElapsed");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "EpochNotElapsed");
+    }
+  });
+
+  // Test: An allowed payee starts their next epoch once the current one has ended
+  it("Lets an allowed payee reset their own epoch", async () => {
+    const before = (await epochOf(allowed)).epochStart.toNumber();
+    await sleep(duration + 1);
+    await selfReset(allowed);
+    const after = await epochOf(allowed);
+    assert.isAtLeast(after.epochStart.toNumber(), before + duration);
+    assert.equal(after.spentAmount.toNumber(), 0);
+  });
+
+  // Test: Payees without permission, and the admin, cannot self-reset
+  it("Rejects payees without permission and the admin", async () => {
+    for (const signer of [denied, admin]) {
+      try {
+        await selfReset(signer);
+        assert.fail("Should have failed with UnauthorizedPayee");
+      } catch (error) {
+        assert.equal(error.error.errorCode.code, "UnauthorizedPayee");
+      }
+    }
+  });
+});
+
+// Test suite: checking the stored PDA bumps
+describe("devquest-vault bump validation", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+
+  before(async () => {
+    await airdrop(provider, admin.publicKey, 2);
+    for (const nonce of [0, 1]) {
+      await program.methods
+        .initialize(NO_NAME, new anchor.BN(nonce))
+        .accountsPartial({
+          user: admin.publicKey,
+          ...deriveVaultAccounts(program.programId, admin.publicKey, nonce),
+        })
+        .signers([admin])
+        .rpc();
+    }
+  });
+
+  // Test: Freshly initialized vaults store the canonical bumps
+  it("Passes on freshly initialized vaults", async () => {
+    for (const nonce of [0, 1]) {
+      const { vaultState } = deriveVaultAccounts(program.programId, admin.publicKey, nonce);
+      await program.methods
+        .validateBumps()
+        .accountsPartial({ user: admin.publicKey, vaultState })
+        .signers([admin])
+        .rpc();
+    }
+  });
+
+  // Test: Only the admin may run the check
+  it("Rejects a non-admin", async () => {
+    const stranger = Keypair.generate();
+    await airdrop(provider, stranger.publicKey, 1);
+    const { vaultState } = deriveVaultAccounts(program.programId, admin.publicKey);
+    try {
+      await program.methods
+        .validateBumps()
+        .accountsPartial({ user: stranger.publicKey, vaultState })
+        .signers([stranger])
+        .rpc();
+      assert.fail("Should have failed with UnauthorizedAdmin");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "UnauthorizedAdmin");
+    }
+  });
+});
+
+// Test suite: keepers claiming on behalf of payees
+describe("devquest-vault claim delegation", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const payee = Keypair.generate();
+  const keeper = Keypair.generate();
+  const stranger = Keypair.generate();
+  const { vaultState, vault } = deriveVaultAccounts(program.programId, admin.publicKey);
+  const amount = 0.1 * anchor.web3.LAMPORTS_PER_SOL;
+  const interval = 60;
+
+  const claimAs = (signer: Keypair) =>
+    program.methods
+      .claimPayoutDelegated()
+      .accountsPartial({ user: signer.publicKey, vaultState, vault, payee: payee.publicKey })
+      .signers([signer])
+      .rpc();
+
+  before(async () => {
+    await createFundedVault({
+      admin,
+      payees: [payee],
+      deposit: 1 * anchor.web3.LAMPORTS_PER_SOL,
+    });
+    for (const kp of [keeper, stranger]) {
+      await airdrop(provider, kp.publicKey, 1);
+    }
+    await program.methods
+      .setMinInterval(new anchor.BN(interval))
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+    await program.methods
+      .schedulePayout(
+        payee.publicKey,
+        new anchor.BN(amount),
+        new anchor.BN(Math.floor(Date.now() / 1000) + 2),
+        new anchor.BN(interval)
+      )
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+    // The payee registers the keeper themselves
+    await program.methods
+      .setClaimDelegate(payee.publicKey, keeper.publicKey)
+      .accountsPartial({ user: payee.publicKey, vaultState })
+      .signers([payee])
+      .rpc();
+    await sleep(3);
+  });
+
+  // Test: Only the registered delegate may claim for the payee
+  it("Rejects a signer that is not the delegate", async () => {
+    try {
+      await claimAs(stranger);
+      assert.fail("Should have failed with UnauthorizedDelegate");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "UnauthorizedDelegate");
+    }
+  });
+
+  // Test: Nobody but the payee or admin can register a delegate
+  it("Rejects setting a delegate for someone else", async () => {
+    try {
+      await program.methods
+        .setClaimDelegate(payee.publicKey, stranger.publicKey)
+        .accountsPartial({ user: stranger.publicKey, vaultState })
+        .signers([stranger])
+        .rpc();
+      assert.fail("Should have failed with UnauthorizedPayee");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "UnauthorizedPayee");
+    }
+  });
+
+  // Test: The keeper triggers the claim and the payout still lands with the payee
+  it("Lets the delegate trigger the payee's claim", async () => {
+    const payeeBefore = await provider.connection.getBalance(payee.publicKey);
+    const keeperBefore = await provider.connection.getBalance(keeper.publicKey);
+    await claimAs(keeper);
+
+    assert.equal(await provider.connection.getBalance(payee.publicKey), payeeBefore + amount);
+    // The keeper only paid the transaction fee
+    assert.isBelow(await provider.connection.getBalance(keeper.publicKey), keeperBefore);
+    const state = await program.account.vaultState.fetch(vaultState);
+    assert.isAbove(state.payoutSchedules[0].nextPayoutTime.toNumber(), Math.floor(Date.now() / 1000));
+  });
+});
+
+// Test suite: pausing the vault when outflow spikes
+describe("devquest-vault circuit breaker", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const { vaultState, vault } = deriveVaultAccounts(program.programId, admin.publicKey);
+  const large = 0.6 * anchor.web3.LAMPORTS_PER_SOL;
+
+  const withdraw = () =>
+    program.methods
+      .withdraw(new anchor.BN(large))
+      .accountsPartial({ user: admin.publicKey, vaultState, vault })
+      .signers([admin])
+      .rpc({ commitment: "confirmed" });
+
+  before(async () => {
+    await createFundedVault({
+      admin,
+      deposit: 3 * anchor.web3.LAMPORTS_PER_SOL,
+    });
+    await program.methods
+      .setVelocityLimit(new anchor.BN(1 * anchor.web3.LAMPORTS_PER_SOL), new anchor.BN(3600))
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+  });
+
+  // Test: Rapid large withdrawals pause the vault once the rolling outflow passes the limit
+  it("Trips on rapid large withdrawals", async () => {
+    await withdraw();
+    assert.isFalse((await program.account.vaultState.fetch(vaultState)).isPaused);
+
+    const tx = await withdraw();
+    const tripped = findEvent(await fetchEvents(program, tx), "CircuitBreakerTripped");
+    assert.ok(tripped, "CircuitBreakerTripped should be emitted");
+    // The first withdrawal has barely decayed a few seconds into an hour-long window
+    assert.isAbove(tripped.data.outflow.toNumber(), 1 * anchor.web3.LAMPORTS_PER_SOL);
+    assert.isAtMost(tripped.data.outflow.toNumber(), 2 * large);
+    assert.isTrue((await program.account.vaultState.fetch(vaultState)).isPaused);
+
+    try {
+      await withdraw();
+      assert.fail("Should have failed with VaultPaused");
+    } catch (error) {
+      assert.equal(error.error.errorCode.code, "VaultPaused");
+    }
+  });
+
+  // Test: The admin resumes explicitly, starting the outflow count afresh
+  it("Resumes only when the admin unpauses", async () => {
+    await program.methods
+      .setPaused(false)
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+    const state = await program.account.vaultState.fetch(vaultState);
+    assert.isFalse(state.isPaused);
+    assert.equal(state.velocityOutflow.toNumber(), 0);
+
+    await withdraw();
+    assert.isFalse((await program.account.vaultState.fetch(vaultState)).isPaused);
+  });
+
+  // Test: Skimming is outflow too and can trip the breaker
+  it("Counts skimmed funds towards the outflow", async () => {
+    const tx = await program.methods
+      .skim()
+      .accountsPartial({ user: admin.publicKey, vaultState, vault })
+      .signers([admin])
+      .rpc({ commitment: "confirmed" });
+    assert.ok(
+      findEvent(await fetchEvents(program, tx), "CircuitBreakerTripped"),
+      "CircuitBreakerTripped should be emitted"
+    );
+    assert.isTrue((await program.account.vaultState.fetch(vaultState)).isPaused);
+  });
+});
+
+// Test suite: pruning inactive schedules
+describe("devquest-vault schedule pruning", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.DevquestVault as Program<DevquestVault>;
+
+  const admin = Keypair.generate();
+  const payees = [Keypair.generate(), Keypair.generate(), Keypair.generate()];
+  const { vaultState, vault } = deriveVaultAccounts(program.programId, admin.publicKey);
+  const amount = 1000;
+  const interval = 2;
+
+  before(async () => {
+    await createFundedVault({
+      admin,
+      payees,
+      deposit: 1 * anchor.web3.LAMPORTS_PER_SOL,
+    });
+    await program.methods
+      .setMinInterval(new anchor.BN(interval))
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+    const startTime = new anchor.BN(Math.floor(Date.now() / 1000) + 2);
+    for (const payee of payees) {
+      await program.methods
+        .schedulePayout(payee.publicKey, new anchor.BN(amount), startTime, new anchor.BN(interval))
+        .accountsPartial({ user: admin.publicKey, vaultState })
+        .signers([admin])
+        .rpc();
+    }
+    await program.methods
+      .cancelPayout(payees[1].publicKey)
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc();
+  });
+
+  // Test: Only the cancelled schedule is removed, and the count comes back as return data
+  it("Removes only inactive schedules", async () => {
+    const signature = await program.methods
+      .pruneSchedules()
+      .accountsPartial({ user: admin.publicKey, vaultState })
+      .signers([admin])
+      .rpc({ commitment: "confirmed" });
+
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const prefix = `Program return: ${program.programId.toBase58()} `;
+    const returned = tx.meta.logMessages.find((log) => log.startsWith(prefix));
+    assert.equal(Buffer.from(returned.slice(prefix.length), "base64").readUInt16LE(0), 1);
+
+    const state = await program.account.vaultState.fetch(vaultState);
+    assert.equal(state.payoutSchedules.length, 2);
+    assert.isTrue(state.payoutSchedules.every((s) => s.isActive));
+    assert.ok(state.payoutSchedules[0].payee.equals(payees[0].publicKey));
+    assert.ok(state.payoutSchedules[1].payee.equals(payees[2].publicKey));
+  });
+
+  // Test: Schedules that survived the prune can still be claimed
+  it("Keeps the remaining schedules claimable", async () => {
+    await sleep(3);
+    const payee = payees[2];
+    const tx = await program.methods
+      .claimPayout()
+      .accountsPartial({ user: payee.publicKey, vaultState, vault })
+      .signers([payee])
+      .rpc({ commitment: "confirmed" });
+    const claimed = findEvent(await fetchEvents(program, tx), "PayoutClaimedEvent");
+    assert.ok(claimed.data.payee.equals(payee.publicKey));
+    assert.isAbove(claimed.data.amount.toNumber(), 0);
+    assert.equal(claimed.data.amount.toNumber() % amount, 0);
+  });
+});
\ No newline at end of file